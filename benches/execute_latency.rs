@@ -0,0 +1,19 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::future::lazy;
+
+/// Measures the round trip of a no-op fiber from a blocked caller.
+fn execute_latency(c: &mut Criterion) {
+    fibers_global::warm_up();
+
+    let mut group = c.benchmark_group("execute_latency");
+    group.bench_function("execute", |b| {
+        b.iter(|| fibers_global::execute(lazy(|| Ok::<_, ()>(()))))
+    });
+    group.bench_function("execute_local", |b| {
+        b.iter(|| fibers_global::execute_local(lazy(|| Ok::<_, ()>(()))))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, execute_latency);
+criterion_main!(benches);
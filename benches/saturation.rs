@@ -0,0 +1,58 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use fibers_global::WaitPolicy;
+use futures::future::lazy;
+use std::hint::black_box;
+use std::thread;
+use std::time::Duration;
+
+const WORK: u64 = 200_000;
+
+fn cpu_bound() -> impl futures::Future<Item = u64, Error = ()> + Send + 'static {
+    lazy(|| Ok((0..WORK).fold(0u64, |acc, x| black_box(acc ^ x))))
+}
+
+/// Runs twice as many blocked callers as the worker threads, each executing CPU-bound fibers.
+fn saturate<F>(execute: F)
+where
+    F: Fn() + Send + Sync + Copy + 'static,
+{
+    let callers = (0..fibers_global::thread_count() * 2)
+        .map(|_| {
+            thread::spawn(move || {
+                for _ in 0..4 {
+                    execute();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for caller in callers {
+        caller.join().unwrap();
+    }
+}
+
+fn execute_under_saturation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("saturation");
+    group.bench_function("spin", |b| {
+        b.iter(|| {
+            saturate(|| {
+                fibers_global::execute_with_poll_interval(cpu_bound(), Duration::from_secs(0))
+                    .unwrap();
+            })
+        })
+    });
+    group.bench_function("park", |b| {
+        b.iter(|| saturate(|| fibers_global::execute(cpu_bound()).map(drop).unwrap()))
+    });
+    group.bench_function("yield_then_park", |b| {
+        b.iter(|| {
+            saturate(|| {
+                let policy = WaitPolicy::yield_then_park(16, Some(Duration::from_millis(1)));
+                fibers_global::execute_with_policy(cpu_bound(), policy).unwrap();
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, execute_under_saturation);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fibers::Spawn;
+use std::thread;
+
+const FIBERS: usize = 1000;
+const THREADS: usize = 4;
+
+fn spawn_throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spawn");
+    group.bench_function("handle", |b| {
+        b.iter(|| {
+            for _ in 0..FIBERS {
+                fibers_global::handle().spawn(futures::finished(()));
+            }
+        })
+    });
+    group.bench_function("with_handle", |b| {
+        b.iter(|| {
+            for _ in 0..FIBERS {
+                fibers_global::with_handle(|h| h.spawn(futures::finished(())));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn handle_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("handle_cache");
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            for _ in 0..FIBERS {
+                black_box(fibers_global::handle());
+            }
+        })
+    });
+    group.bench_function("uncached", |b| {
+        b.iter(|| {
+            for _ in 0..FIBERS {
+                // Clones the handle while holding the lock of the global executor.
+                black_box(fibers_global::with_handle(|h| h.clone()));
+            }
+        })
+    });
+    group.bench_function("cached_contended", |b| {
+        b.iter(|| spawn_from_threads(|| fibers_global::handle().spawn(futures::finished(()))))
+    });
+    group.bench_function("uncached_contended", |b| {
+        b.iter(|| {
+            spawn_from_threads(|| {
+                fibers_global::with_handle(|h| h.clone()).spawn(futures::finished(()))
+            })
+        })
+    });
+    group.finish();
+}
+
+/// Calls `spawn` `FIBERS` times on each of `THREADS` threads.
+fn spawn_from_threads(spawn: fn()) {
+    let threads = (0..THREADS)
+        .map(|_| {
+            thread::spawn(move || {
+                for _ in 0..FIBERS {
+                    spawn();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap();
+    }
+}
+
+criterion_group!(benches, spawn_throughput, handle_cache);
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use futures::future::lazy;
+use std::time::Duration;
+
+const FIBERS: usize = 1000;
+
+/// Spawns no-op fibers and waits until all of them complete.
+fn drain_throughput(c: &mut Criterion) {
+    fibers_global::warm_up();
+
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Elements(FIBERS as u64));
+    group.bench_function("spawn", |b| {
+        b.iter(|| {
+            for _ in 0..FIBERS {
+                fibers_global::spawn(lazy(|| Ok(())));
+            }
+            assert!(fibers_global::wait_for_idle(Duration::from_secs(10)));
+        })
+    });
+    group.bench_function("spawn_bounded", |b| {
+        let max_in_flight = fibers_global::thread_count() * 4;
+        b.iter(|| {
+            for _ in 0..FIBERS {
+                fibers_global::spawn_bounded(lazy(|| Ok(())), max_in_flight);
+            }
+            assert!(fibers_global::wait_for_idle(Duration::from_secs(10)));
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, drain_throughput);
+criterion_main!(benches);
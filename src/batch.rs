@@ -0,0 +1,512 @@
+use fibers::sync::oneshot::{Monitor, MonitorError};
+use futures::future::{self, Either as FutureEither};
+use futures::stream::{self, Stream};
+use futures::Future;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::metrics::BlockingGuard;
+use crate::{
+    execute, execute_checked, into_execute_result, spawn_monitor, spawn_task_monitor, Either,
+    ExecuteError, ExecutorAborted,
+};
+
+/// Executes the given futures concurrently by using the global `ThreadPoolExecutor` and waits all the results.
+///
+/// Each future is executed on its own fiber.
+/// The items are returned in the same order as `futures` regardless of the completion order.
+///
+/// If any of the futures fails, this function returns the error immediately
+/// (the remaining fibers keep running but their results are discarded).
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::{err, ok};
+///
+/// let result = fibers_global::execute_all(vec![ok::<_, ()>(1), ok(2), ok(3)]);
+/// assert_eq!(result, Ok(vec![1, 2, 3]));
+///
+/// let result = fibers_global::execute_all(vec![ok(1), err("failed"), ok(3)]);
+/// assert_eq!(result, Err("failed"));
+/// ```
+pub fn execute_all<I, F>(futures: I) -> Result<Vec<F::Item>, F::Error>
+where
+    I: IntoIterator<Item = F>,
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let monitors = spawn_all(futures);
+    into_execute_result(execute(future::join_all(monitors)))
+}
+
+/// Executes the given futures by using the global `ThreadPoolExecutor`, keeping at most `max_concurrent` of them
+/// in flight, and waits all the results.
+///
+/// This is the same as [`execute_all`] except that each fiber is spawned only after one of the previous ones
+/// completes, so that a large number of futures does not overwhelm downstream resources.
+/// The items are returned in the same order as `futures` regardless of the completion order.
+///
+/// If any of the futures fails, this function returns the error immediately
+/// (the futures not yet spawned are dropped without being polled).
+///
+/// # Panics
+///
+/// If `max_concurrent` is `0` or the global executor aborted, the current thread will panic.
+///
+/// [`execute_all`]: ./fn.execute_all.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::ok;
+///
+/// let futures = (0..10).map(|i| ok::<_, ()>(i * 2));
+/// let result = fibers_global::execute_all_limited(futures, 3);
+/// assert_eq!(result, Ok((0..10).map(|i| i * 2).collect::<Vec<_>>()));
+/// ```
+pub fn execute_all_limited<I, F>(
+    futures: I,
+    max_concurrent: usize,
+) -> Result<Vec<F::Item>, F::Error>
+where
+    I: IntoIterator<Item = F>,
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    assert_ne!(max_concurrent, 0);
+    let _guard = BlockingGuard::new();
+    let monitors = stream::iter_ok(futures)
+        .map(spawn_task_monitor)
+        .buffered(max_concurrent);
+
+    // `Buffered` requires a task context that fibers do not provide (see `execute_batch_unordered`).
+    let result = futures::executor::spawn(monitors.collect()).wait_future();
+    result.map_err(|e| match e {
+        MonitorError::Failed(e) => e,
+        MonitorError::Aborted => panic!("{}", ExecutorAborted),
+    })
+}
+
+/// Executes the given futures concurrently by using the global `ThreadPoolExecutor` and waits all the results
+/// without short-circuiting.
+///
+/// Unlike [`execute_all`], a failing future does not prevent the others from being waited:
+/// one `Result` is returned for each of `futures` in the submission order, regardless of the completion order.
+/// This is similar to JavaScript's `Promise.allSettled`.
+///
+/// # Panics
+///
+/// If the global executor aborted, the current thread will panic.
+///
+/// [`execute_all`]: ./fn.execute_all.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::{err, ok};
+///
+/// let results = fibers_global::execute_all_settled(vec![ok(1), err("failed"), ok(3)]);
+/// assert_eq!(results, vec![Ok(1), Err("failed"), Ok(3)]);
+/// ```
+pub fn execute_all_settled<I, F>(futures: I) -> Vec<Result<F::Item, F::Error>>
+where
+    I: IntoIterator<Item = F>,
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let monitors = spawn_all(futures)
+        .into_iter()
+        .map(|monitor| monitor.then(Ok::<_, ()>));
+    let results = execute(future::join_all(monitors)).unwrap_or_else(|()| unreachable!());
+    results.into_iter().map(into_execute_result).collect()
+}
+
+/// Executes the given keyed futures concurrently by using the global `ThreadPoolExecutor` and waits all the results,
+/// which are returned in a map by the keys.
+///
+/// This is the same as [`execute_all_settled`] except that the results are associated with the keys instead of the positions.
+/// All the futures are executed even if their keys are duplicate, but only the result of the last one is kept.
+///
+/// # Panics
+///
+/// If the global executor aborted, the current thread will panic.
+///
+/// [`execute_all_settled`]: ./fn.execute_all_settled.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::{err, ok};
+///
+/// let results = fibers_global::execute_keyed(vec![("a", ok(1)), ("b", err("failed"))]);
+/// assert_eq!(results["a"], Ok(1));
+/// assert_eq!(results["b"], Err("failed"));
+/// ```
+pub fn execute_keyed<I, K, F>(jobs: I) -> HashMap<K, Result<F::Item, F::Error>>
+where
+    I: IntoIterator<Item = (K, F)>,
+    K: Eq + Hash,
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let (keys, futures): (Vec<_>, Vec<_>) = jobs.into_iter().unzip();
+    keys.into_iter().zip(execute_all_settled(futures)).collect()
+}
+
+/// Executes the given futures concurrently by using the global `ThreadPoolExecutor`
+/// and calls `on_result` with each result in the completion order.
+///
+/// Each future is executed on its own fiber. This function blocks until all of them complete,
+/// so downstream work can be overlapped with the remaining futures unlike [`execute_all`].
+///
+/// `on_result` is called on the calling thread. While it is running, the subsequent results are
+/// just buffered, so it should not block for too long.
+///
+/// # Panics
+///
+/// If the global executor aborted, the current thread will panic.
+///
+/// [`execute_all`]: ./fn.execute_all.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::{err, ok};
+///
+/// let mut results = Vec::new();
+/// fibers_global::execute_batch_unordered(vec![ok(1), err("failed"), ok(3)], |r| results.push(r));
+/// results.sort();
+/// assert_eq!(results, vec![Ok(1), Ok(3), Err("failed")]);
+/// ```
+pub fn execute_batch_unordered<I, F, G>(futures: I, mut on_result: G)
+where
+    I: IntoIterator<Item = F>,
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+    G: FnMut(Result<F::Item, F::Error>),
+{
+    let _guard = BlockingGuard::new();
+    let monitors = stream::futures_unordered(futures.into_iter().map(spawn_task_monitor));
+
+    // `FuturesUnordered` requires a task context that fibers do not provide,
+    // so it is driven on the calling thread (`TaskMonitor` notifies non-fiber tasks).
+    let mut results = futures::executor::spawn(monitors.then(Ok::<_, ()>));
+    while let Some(result) = results.wait_stream() {
+        match result {
+            Err(()) | Ok(Err(MonitorError::Aborted)) => panic!("{}", ExecutorAborted),
+            Ok(Err(MonitorError::Failed(e))) => on_result(Err(e)),
+            Ok(Ok(v)) => on_result(Ok(v)),
+        }
+    }
+}
+
+/// Executes the given two futures by using the global `ThreadPoolExecutor` and waits both results.
+///
+/// This is a shorthand of `execute(a.join(b))`.
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::ok;
+///
+/// let result = fibers_global::execute_join2(ok::<_, ()>(1), ok("two"));
+/// assert_eq!(result, Ok((1, "two")));
+/// ```
+pub fn execute_join2<A, B>(a: A, b: B) -> Result<(A::Item, B::Item), A::Error>
+where
+    A: Future + Send + 'static,
+    B: Future<Error = A::Error> + Send + 'static,
+    A::Item: Send + 'static,
+    B::Item: Send + 'static,
+    A::Error: Send + 'static,
+{
+    execute(a.join(b))
+}
+
+/// Executes the given three futures by using the global `ThreadPoolExecutor` and waits all the results.
+///
+/// This is a shorthand of `execute(a.join3(b, c))`.
+pub fn execute_join3<A, B, C, T, U, V, E>(a: A, b: B, c: C) -> Result<(T, U, V), E>
+where
+    A: Future<Item = T, Error = E> + Send + 'static,
+    B: Future<Item = U, Error = E> + Send + 'static,
+    C: Future<Item = V, Error = E> + Send + 'static,
+    T: Send + 'static,
+    U: Send + 'static,
+    V: Send + 'static,
+    E: Send + 'static,
+{
+    execute(a.join3(b, c))
+}
+
+/// Executes the given two futures by using the global `ThreadPoolExecutor` and waits the first one to complete.
+///
+/// The result of the winner is returned as `Either::Left` (for `a`) or `Either::Right` (for `b`),
+/// and the other future is dropped without being completed. If both are ready at the same time, `a` wins.
+///
+/// # Examples
+///
+/// ```
+/// use fibers_global::Either;
+/// use futures::future::{empty, ok};
+///
+/// let result = fibers_global::execute_select2(empty::<(), ()>(), ok::<_, ()>(1));
+/// assert_eq!(result, Ok(Either::Right(1)));
+/// ```
+pub fn execute_select2<A, B, T, U, E, G>(a: A, b: B) -> Result<Either<T, U>, Either<E, G>>
+where
+    A: Future<Item = T, Error = E> + Send + 'static,
+    B: Future<Item = U, Error = G> + Send + 'static,
+    T: Send + 'static,
+    U: Send + 'static,
+    E: Send + 'static,
+    G: Send + 'static,
+{
+    execute(a.select2(b).then(|result| match result {
+        Ok(FutureEither::A((v, _))) => Ok(Either::Left(v)),
+        Ok(FutureEither::B((v, _))) => Ok(Either::Right(v)),
+        Err(FutureEither::A((e, _))) => Err(Either::Left(e)),
+        Err(FutureEither::B((e, _))) => Err(Either::Right(e)),
+    }))
+}
+
+/// Spawns a fiber for each of the given futures by using the global `ThreadPoolExecutor` and
+/// returns futures to monitor their execution results.
+///
+/// The monitors are returned in the same order as `futures`.
+pub fn spawn_all<I, F>(futures: I) -> Vec<Monitor<F::Item, F::Error>>
+where
+    I: IntoIterator<Item = F>,
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    futures.into_iter().map(spawn_monitor).collect()
+}
+
+/// Waits for all the given monitors by using the global `ThreadPoolExecutor` and collects their results.
+///
+/// This is the counterpart of [`spawn_all`]: the items are returned in the same order as `monitors`,
+/// and if any of the monitored fibers fails, its error is returned immediately.
+///
+/// # Panics
+///
+/// Like [`execute`], if the global executor or any of the monitored fibers aborted, the current thread will panic.
+///
+/// [`spawn_all`]: ./fn.spawn_all.html
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::{err, ok};
+///
+/// let monitors = fibers_global::spawn_all(vec![ok::<_, ()>(1), ok(2), ok(3)]);
+/// assert_eq!(fibers_global::join_monitors(monitors), Ok(vec![1, 2, 3]));
+///
+/// let monitors = fibers_global::spawn_all(vec![ok(1), err("failed")]);
+/// assert_eq!(fibers_global::join_monitors(monitors), Err("failed"));
+/// ```
+pub fn join_monitors<T, E>(monitors: Vec<Monitor<T, E>>) -> Result<Vec<T>, E>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    match execute_checked(future::join_all(monitors)) {
+        Ok(items) => Ok(items),
+        Err(ExecuteError::Failed(MonitorError::Failed(e))) => Err(e),
+        Err(ExecuteError::Failed(MonitorError::Aborted)) | Err(ExecuteError::Aborted) => {
+            panic!("{}", ExecutorAborted)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fibers::time::timer;
+    use futures::Future;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn execute_all_limited_works() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let delays = [30, 5, 20, 0, 15, 10, 25, 1];
+        let futures = delays.iter().enumerate().map(|(i, &delay)| {
+            let in_flight = Arc::clone(&in_flight);
+            let max_in_flight = Arc::clone(&max_in_flight);
+            let counter = Arc::clone(&in_flight);
+            future::lazy(move || {
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(n, Ordering::SeqCst);
+                timer::timeout(Duration::from_millis(delay))
+            })
+            .then(move |result| {
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                result.map(|()| i)
+            })
+        });
+        let result = execute_all_limited(futures, 2).map_err(|_| ());
+        assert_eq!(result, Ok((0..delays.len()).collect::<Vec<_>>()));
+        assert!(max_in_flight.load(Ordering::SeqCst) <= 2);
+
+        // The futures after a failure are not started.
+        let started = Arc::new(AtomicUsize::new(0));
+        let futures = (0..10).map(|i| {
+            let started = Arc::clone(&started);
+            future::lazy(move || {
+                started.fetch_add(1, Ordering::SeqCst);
+                if i == 1 {
+                    Err("failed")
+                } else {
+                    Ok(i)
+                }
+            })
+        });
+        assert_eq!(execute_all_limited(futures, 1), Err("failed"));
+        assert_eq!(started.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn execute_keyed_works() {
+        let delayed = |ms, v| {
+            timer::timeout(Duration::from_millis(ms))
+                .map_err(|_| "timer")
+                .and_then(move |()| v)
+        };
+        let results = execute_keyed(vec![
+            ("a", delayed(30, Ok(1))),
+            ("b", delayed(0, Err("failed"))),
+            ("c", delayed(15, Ok(3))),
+        ]);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results["a"], Ok(1));
+        assert_eq!(results["b"], Err("failed"));
+        assert_eq!(results["c"], Ok(3));
+
+        // The last one wins.
+        let results = execute_keyed(vec![("a", delayed(10, Ok(1))), ("a", delayed(0, Ok(2)))]);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results["a"], Ok(2));
+    }
+
+    #[test]
+    fn execute_join3_works() {
+        let a = timer::timeout(Duration::from_millis(10))
+            .map(|()| 1)
+            .map_err(|_| "a");
+        let b = future::ok(2);
+        let c = future::lazy(|| Ok("c"));
+        assert_eq!(execute_join3(a, b, c), Ok((1, 2, "c")));
+
+        let result = execute_join3(
+            future::ok(1),
+            future::err::<(), _>("b"),
+            future::empty::<(), _>(),
+        );
+        assert_eq!(result, Err("b"));
+    }
+
+    #[test]
+    fn execute_select2_works() {
+        let (tx, rx) = fibers::sync::oneshot::channel::<()>();
+        let a = timer::timeout(Duration::from_secs(60)).then(move |_| {
+            let _ = tx;
+            Ok::<_, ()>("a")
+        });
+        let b = timer::timeout(Duration::from_millis(1)).then(|_| Ok::<_, ()>("b"));
+        assert_eq!(execute_select2(a, b), Ok(Either::Right("b")));
+
+        // The loser has been dropped.
+        assert!(execute(rx).is_err());
+
+        let result = execute_select2(future::err::<(), _>("a"), future::empty::<(), ()>());
+        assert_eq!(result, Err(Either::Left("a")));
+    }
+
+    #[test]
+    fn execute_batch_unordered_works() {
+        let futures = (0..5u64).map(|i| {
+            timer::timeout(Duration::from_millis((5 - i) * 10))
+                .map(move |()| i)
+                .map_err(|_| ())
+        });
+        let mut order = Vec::new();
+        execute_batch_unordered(futures, |r| order.push(r.unwrap()));
+        assert_eq!(order, vec![4, 3, 2, 1, 0]);
+
+        let mut called = false;
+        execute_batch_unordered(Vec::<future::FutureResult<(), ()>>::new(), |_| {
+            called = true
+        });
+        assert!(!called);
+    }
+
+    #[test]
+    fn execute_all_preserves_order() {
+        let futures = (0..5u64).map(|i| {
+            // Completes in the reverse order.
+            timer::timeout(Duration::from_millis((5 - i) * 5)).then(move |_| Ok::<_, ()>(i))
+        });
+        assert_eq!(execute_all(futures), Ok(vec![0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn execute_all_settled_preserves_order() {
+        let futures = (0..6u64).map(|i| {
+            // Completes in the reverse order, failing at odd indices.
+            timer::timeout(Duration::from_millis((6 - i) * 5)).then(move |_| {
+                if i % 2 == 0 {
+                    Ok(i)
+                } else {
+                    Err(i)
+                }
+            })
+        });
+        assert_eq!(
+            execute_all_settled(futures),
+            vec![Ok(0), Err(1), Ok(2), Err(3), Ok(4), Err(5)]
+        );
+
+        let empty = Vec::<future::FutureResult<(), ()>>::new();
+        assert!(execute_all_settled(empty).is_empty());
+    }
+
+    #[test]
+    fn spawn_all_preserves_order() {
+        let futures = (0..5u64).map(|i| {
+            timer::timeout(Duration::from_millis((5 - i) * 5)).then(move |_| Ok::<_, ()>(i))
+        });
+        let mut monitors = spawn_all(futures);
+        assert_eq!(monitors.len(), 5);
+        assert_eq!(execute(monitors.remove(3)).ok(), Some(3));
+        assert_eq!(execute(monitors.remove(0)).ok(), Some(0));
+    }
+
+    #[test]
+    fn join_monitors_works() {
+        let futures = (0..5u64).map(|i| {
+            timer::timeout(Duration::from_millis((5 - i) * 5)).then(move |_| Ok::<_, ()>(i))
+        });
+        assert_eq!(join_monitors(spawn_all(futures)), Ok(vec![0, 1, 2, 3, 4]));
+
+        let futures = (0..5u64).map(|i| {
+            timer::timeout(Duration::from_millis(i * 5))
+                .then(move |_| if i == 1 { Err(i) } else { Ok(i) })
+        });
+        assert_eq!(join_monitors(spawn_all(futures)), Err(1));
+
+        assert_eq!(join_monitors(Vec::<Monitor<(), ()>>::new()), Ok(Vec::new()));
+    }
+}
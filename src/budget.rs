@@ -0,0 +1,108 @@
+use futures::{Async, Future, Poll};
+
+use crate::{execute, StalledError};
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result,
+/// allowing the future to be polled at most `max_polls` times.
+///
+/// If the future is about to be polled more than that, it is dropped and `Err(StalledError)` is returned.
+/// This detects (hand-written) futures that keep returning `NotReady` while waking themselves up
+/// without making progress (e.g., by `fibers::fiber::yield_poll` or by notifying their own task), which spin forever.
+/// Unlike `execute_timeout`, the limit does not depend on the wall-clock time,
+/// so it is stable under CPU contention.
+///
+/// Note that a future returning `NotReady` without arranging to be woken up at all is never polled again,
+/// so it is not detected by this function (use `execute_timeout` instead).
+///
+/// Like [`execute`], this function panics if the global executor aborted.
+///
+/// # Panics
+///
+/// If `max_polls` is `0`, the current thread will panic.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// use fibers::fiber;
+/// use fibers_global::StalledError;
+/// use futures::future::{ok, poll_fn};
+///
+/// assert_eq!(fibers_global::execute_with_budget(ok::<_, ()>(1), 1), Ok(Ok(1)));
+///
+/// let stuck = poll_fn(fiber::yield_poll::<(), ()>);
+/// assert_eq!(fibers_global::execute_with_budget(stuck, 100), Err(StalledError));
+/// ```
+pub fn execute_with_budget<F>(
+    future: F,
+    max_polls: usize,
+) -> Result<Result<F::Item, F::Error>, StalledError>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    assert_ne!(max_polls, 0);
+    execute(Budgeted {
+        future,
+        remaining: max_polls,
+    })
+}
+
+/// A future that fails with `StalledError` if the inner future is polled more than the budget.
+struct Budgeted<F> {
+    future: F,
+    remaining: usize,
+}
+impl<F: Future> Future for Budgeted<F> {
+    type Item = Result<F::Item, F::Error>;
+    type Error = StalledError;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if self.remaining == 0 {
+            return Err(StalledError);
+        }
+        self.remaining -= 1;
+        match self.future.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(v)) => Ok(Async::Ready(Ok(v))),
+            Err(e) => Ok(Async::Ready(Err(e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fibers::time::timer;
+    use futures::future::{lazy, poll_fn};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn execute_with_budget_works() {
+        let future = timer::timeout(Duration::from_millis(5)).map(|()| 1);
+        assert_eq!(execute_with_budget(future, 10).map_err(|_| ()), Ok(Ok(1)));
+
+        let future = lazy(|| Err::<(), _>("failed"));
+        assert_eq!(execute_with_budget(future, 1), Ok(Err("failed")));
+    }
+
+    #[test]
+    fn stalled_future_stops_at_budget() {
+        // Always returns `NotReady` but is re-polled immediately.
+        let polls = Arc::new(AtomicUsize::new(0));
+        let stuck = {
+            let polls = Arc::clone(&polls);
+            poll_fn(move || {
+                polls.fetch_add(1, Ordering::SeqCst);
+                fibers::fiber::yield_poll::<(), ()>()
+            })
+        };
+        assert_eq!(execute_with_budget(stuck, 100), Err(StalledError));
+        assert_eq!(polls.load(Ordering::SeqCst), 100);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::global;
+
+/// A builder to configure the global executor before it starts.
+///
+/// The stack size of the scheduler threads cannot be configured, since `fibers::ThreadPoolExecutor`
+/// spawns them by itself with the default stack size of `std::thread`.
+/// That default can be changed by the `RUST_MIN_STACK` environment variable of the process.
+///
+/// # Examples
+///
+/// ```
+/// use fibers_global::GlobalExecutorBuilder;
+///
+/// # if cfg!(feature = "no-threads") { return; }
+/// let initialized = GlobalExecutorBuilder::new()
+///     .thread_count(2)
+///     .thread_name_prefix("my-executor")
+///     .try_init();
+/// assert!(initialized);
+/// assert_eq!(fibers_global::thread_count(), 2);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct GlobalExecutorBuilder {
+    pub(crate) thread_count: Option<usize>,
+    pub(crate) thread_name_prefix: Option<String>,
+    pub(crate) max_restarts: Option<usize>,
+}
+impl GlobalExecutorBuilder {
+    /// Makes a new `GlobalExecutorBuilder` that has no options set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of scheduler threads used by the global executor.
+    ///
+    /// The default value is the number of physical CPUs capped by `std::thread::available_parallelism()`
+    /// (i.e., cgroup quotas are respected). If the latter is unavailable, `num_cpus::get()` is used.
+    ///
+    /// A count greater than [`MAX_THREAD_COUNT`] is clamped to it with a warning
+    /// (e.g., `usize::MAX` computed dynamically).
+    ///
+    /// # Panics
+    ///
+    /// If the specified count is `0`, the current thread will panic.
+    ///
+    /// [`MAX_THREAD_COUNT`]: ./constant.MAX_THREAD_COUNT.html
+    pub fn thread_count(&mut self, n: usize) -> &mut Self {
+        assert_ne!(n, 0);
+        self.thread_count = Some(global::clamp_thread_count(n));
+        self
+    }
+
+    /// Sets the prefix of the names of the threads spawned by the global executor.
+    ///
+    /// The background thread is named `{prefix}-run`.
+    /// On Linux, the `i`-th scheduler thread is also named `{prefix}-{i}` at the OS level
+    /// (truncated to 15 bytes), though `std::thread::current().name()` returns `None` on it.
+    ///
+    /// The default value is `"fibers-global"`.
+    pub fn thread_name_prefix<T: Into<String>>(&mut self, prefix: T) -> &mut Self {
+        self.thread_name_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Sets the maximum number of times the global executor is restarted after it aborted.
+    ///
+    /// The executor aborts if one of its threads dies (e.g., a fiber panicked).
+    /// In that case the fibers remaining on it are dropped and a new executor is built
+    /// with the same configuration, as long as the number of restarts does not exceed `n`.
+    /// Each restart is reported to the standard error.
+    ///
+    /// After the restarts are exhausted, the executor stays aborted until `shutdown` is called
+    /// (e.g., `execute_checked` returns `Err(ExecuteError::Aborted)`).
+    ///
+    /// The default value is `0` (i.e., the executor is never restarted).
+    pub fn max_restarts(&mut self, n: usize) -> &mut Self {
+        self.max_restarts = Some(n);
+        self
+    }
+
+    /// Applies the options to the global executor.
+    ///
+    /// The options that have not been set are left unchanged.
+    ///
+    /// If the global executor already has started,
+    /// the invocation of this method has no effect and `false` is returned.
+    pub fn try_init(&self) -> bool {
+        global::configure(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MAX_THREAD_COUNT;
+
+    #[test]
+    fn thread_count_is_clamped() {
+        let count = |n| GlobalExecutorBuilder::new().thread_count(n).thread_count;
+        assert_eq!(count(1), Some(1));
+        assert_eq!(count(MAX_THREAD_COUNT), Some(MAX_THREAD_COUNT));
+        assert_eq!(count(MAX_THREAD_COUNT + 1), Some(MAX_THREAD_COUNT));
+        assert_eq!(count(usize::MAX), Some(MAX_THREAD_COUNT));
+    }
+
+    #[test]
+    #[should_panic]
+    fn thread_count_rejects_zero() {
+        GlobalExecutorBuilder::new().thread_count(0);
+    }
+}
@@ -0,0 +1,374 @@
+use fibers::sync::oneshot::{self, Monitor, MonitorError, Monitored};
+use fibers::Spawn;
+use futures::{Async, Future, Poll};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, PoisonError};
+
+use crate::{panic_handler, with_spawn_handle, AbortableError};
+
+/// Spawns a fiber that can be cancelled via the returned handle by using the global `ThreadPoolExecutor`.
+///
+/// See [`CancelHandle`] for details.
+///
+/// [`CancelHandle`]: ./struct.CancelHandle.html
+///
+/// # Examples
+///
+/// ```
+/// let handle = fibers_global::spawn_cancellable(futures::empty());
+/// handle.cancel();
+/// ```
+pub fn spawn_cancellable<F>(future: F) -> CancelHandle
+where
+    F: Future<Item = (), Error = ()> + Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    crate::spawn(Cancellable {
+        future,
+        token: CancelToken { rx: Some(rx) },
+    });
+    CancelHandle {
+        tx: Mutex::new(Some(tx)),
+    }
+}
+
+/// A handle to cancel the fiber spawned by [`spawn_cancellable`].
+///
+/// Calling `cancel` wakes up the fiber and its future is dropped without being polled again.
+/// Dropping this handle without calling `cancel` leaves the fiber running to completion.
+///
+/// [`spawn_cancellable`]: ./fn.spawn_cancellable.html
+#[derive(Debug)]
+pub struct CancelHandle {
+    tx: Mutex<Option<oneshot::Sender<()>>>,
+}
+impl CancelHandle {
+    /// Cancels the fiber.
+    ///
+    /// If the fiber has already completed, this does nothing.
+    pub fn cancel(&self) {
+        send_cancel(&self.tx);
+    }
+}
+
+/// Spawns a fiber that can be aborted via the returned handle by using the global `ThreadPoolExecutor`,
+/// and returns a future to monitor it's execution result.
+///
+/// This is the monitored counterpart of [`spawn_cancellable`]:
+/// if [`AbortHandle::abort`] is called before the fiber completes, the future is dropped without being polled
+/// again and the monitor fails with `AbortableError::Aborted`.
+///
+/// [`spawn_cancellable`]: ./fn.spawn_cancellable.html
+/// [`AbortHandle::abort`]: ./struct.AbortHandle.html#method.abort
+///
+/// # Examples
+///
+/// ```
+/// use fibers::sync::oneshot::MonitorError;
+/// use fibers_global::AbortableError;
+///
+/// let (handle, monitor) = fibers_global::spawn_monitor_abortable(futures::empty::<(), ()>());
+/// handle.abort();
+/// assert_eq!(
+///     fibers_global::execute(monitor).err(),
+///     Some(MonitorError::Failed(AbortableError::Aborted))
+/// );
+/// ```
+pub fn spawn_monitor_abortable<F>(
+    future: F,
+) -> (AbortHandle, Monitor<F::Item, AbortableError<F::Error>>)
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    let monitor = crate::spawn_monitor(Abortable {
+        future,
+        token: CancelToken { rx: Some(rx) },
+    });
+    let handle = AbortHandle {
+        tx: Mutex::new(Some(tx)),
+    };
+    (handle, monitor)
+}
+
+/// A handle to abort the fiber spawned by [`spawn_monitor_abortable`].
+///
+/// Calling `abort` wakes up the fiber, and the monitor fails with `AbortableError::Aborted`.
+/// Dropping this handle without calling `abort` leaves the fiber running to completion.
+///
+/// [`spawn_monitor_abortable`]: ./fn.spawn_monitor_abortable.html
+#[derive(Debug)]
+pub struct AbortHandle {
+    tx: Mutex<Option<oneshot::Sender<()>>>,
+}
+impl AbortHandle {
+    /// Aborts the fiber.
+    ///
+    /// If the fiber has already completed, this does nothing (i.e., the monitor yields the actual result).
+    pub fn abort(&self) {
+        send_cancel(&self.tx);
+    }
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns a monitor that aborts the fiber when dropped.
+///
+/// This prevents a background fiber from outliving the scope that cares about its result.
+/// See [`ScopedMonitor`] for details.
+///
+/// [`ScopedMonitor`]: ./struct.ScopedMonitor.html
+///
+/// # Examples
+///
+/// ```
+/// let monitor = fibers_global::spawn_scoped_monitor(futures::lazy(|| Ok::<_, ()>(1)));
+/// assert_eq!(fibers_global::execute(monitor).ok(), Some(1));
+///
+/// // The fiber is aborted.
+/// let monitor = fibers_global::spawn_scoped_monitor(futures::empty::<(), ()>());
+/// drop(monitor);
+/// ```
+pub fn spawn_scoped_monitor<F>(future: F) -> ScopedMonitor<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    let (monitored, monitor) = oneshot::monitor();
+    let future = panic_handler::wrap(Scoped {
+        future,
+        token: CancelToken { rx: Some(rx) },
+        monitored: Some(monitored),
+    });
+    #[cfg(feature = "tracing")]
+    let future = crate::trace::instrument(crate::FiberId::next(), future);
+
+    // Not via `spawn`, which drops the future during `drain_and_shutdown`.
+    with_spawn_handle(future, |h, f| h.spawn(f));
+    ScopedMonitor {
+        monitor,
+        tx: Mutex::new(Some(tx)),
+    }
+}
+
+/// A monitor of the fiber spawned by [`spawn_scoped_monitor`].
+///
+/// This dereferences to (and can be polled as) the underlying `Monitor`.
+/// Dropping this before the fiber completes wakes up the fiber, and its future is dropped without being polled again.
+///
+/// [`spawn_scoped_monitor`]: ./fn.spawn_scoped_monitor.html
+#[derive(Debug)]
+pub struct ScopedMonitor<T, E> {
+    monitor: Monitor<T, E>,
+    tx: Mutex<Option<oneshot::Sender<()>>>,
+}
+impl<T, E> Deref for ScopedMonitor<T, E> {
+    type Target = Monitor<T, E>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.monitor
+    }
+}
+impl<T, E> DerefMut for ScopedMonitor<T, E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.monitor
+    }
+}
+impl<T, E> Future for ScopedMonitor<T, E> {
+    type Item = T;
+    type Error = MonitorError<E>;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        self.monitor.poll()
+    }
+}
+impl<T, E> Drop for ScopedMonitor<T, E> {
+    fn drop(&mut self) {
+        send_cancel(&self.tx);
+    }
+}
+
+fn send_cancel(tx: &Mutex<Option<oneshot::Sender<()>>>) {
+    let tx = tx.lock().unwrap_or_else(PoisonError::into_inner).take();
+    if let Some(tx) = tx {
+        let _ = tx.send(());
+    }
+}
+
+/// The receiving side of `CancelHandle`, `AbortHandle` and `ScopedMonitor`.
+struct CancelToken {
+    rx: Option<oneshot::Receiver<()>>,
+}
+impl CancelToken {
+    fn is_cancelled(&mut self) -> bool {
+        let cancelled = match self.rx.as_mut().map(|rx| rx.poll()) {
+            None | Some(Ok(Async::NotReady)) => return false,
+            Some(Ok(Async::Ready(()))) => true,
+            // The handle has been dropped without cancelling.
+            Some(Err(_)) => false,
+        };
+        self.rx = None;
+        cancelled
+    }
+}
+
+struct Cancellable<F> {
+    future: F,
+    token: CancelToken,
+}
+impl<F: Future<Item = (), Error = ()>> Future for Cancellable<F> {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if self.token.is_cancelled() {
+            return Ok(Async::Ready(()));
+        }
+        self.future.poll()
+    }
+}
+
+struct Abortable<F> {
+    future: F,
+    token: CancelToken,
+}
+impl<F: Future> Future for Abortable<F> {
+    type Item = F::Item;
+    type Error = AbortableError<F::Error>;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if self.token.is_cancelled() {
+            return Err(AbortableError::Aborted);
+        }
+        self.future.poll().map_err(AbortableError::Failed)
+    }
+}
+
+struct Scoped<F: Future> {
+    future: F,
+    token: CancelToken,
+    monitored: Option<Monitored<F::Item, F::Error>>,
+}
+impl<F: Future> Future for Scoped<F> {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if self.token.is_cancelled() {
+            return Ok(Async::Ready(()));
+        }
+        let result = match self.future.poll() {
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Ok(Async::Ready(v)) => Ok(v),
+            Err(e) => Err(e),
+        };
+        if let Some(monitored) = self.monitored.take() {
+            monitored.exit(result);
+        }
+        Ok(Async::Ready(()))
+    }
+}
+
+#[cfg(all(test, not(feature = "no-threads")))]
+mod tests {
+    use futures::{empty, lazy};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::*;
+
+    struct NotifyOnDrop(mpsc::Sender<()>);
+    impl Drop for NotifyOnDrop {
+        fn drop(&mut self) {
+            let _ = self.0.send(());
+        }
+    }
+
+    #[test]
+    fn spawn_cancellable_works() {
+        let (tx, rx) = mpsc::channel();
+        let guard = NotifyOnDrop(tx);
+        let handle = spawn_cancellable(empty().then(move |r| {
+            let _ = &guard;
+            r
+        }));
+        assert!(rx.recv_timeout(Duration::from_millis(10)).is_err());
+
+        handle.cancel();
+        assert!(rx.recv_timeout(Duration::from_secs(10)).is_ok());
+    }
+
+    #[test]
+    fn dropping_handle_does_not_cancel() {
+        let (tx, rx) = mpsc::channel();
+        let (start_tx, start_rx) = oneshot::channel();
+        let handle = spawn_cancellable(start_rx.then(move |_| {
+            let _ = tx.send(());
+            Ok(())
+        }));
+        drop(handle);
+
+        let _ = start_tx.send(());
+        assert!(rx.recv_timeout(Duration::from_secs(10)).is_ok());
+
+        // Cancelling a completed fiber does nothing.
+        let handle = spawn_cancellable(lazy(|| Ok(())));
+        crate::execute(fibers::time::timer::timeout(Duration::from_millis(10))).unwrap();
+        handle.cancel();
+    }
+
+    #[test]
+    fn spawn_monitor_abortable_works() {
+        use fibers::sync::oneshot::MonitorError;
+
+        let (started_tx, started_rx) = mpsc::channel();
+        let (handle, monitor) = spawn_monitor_abortable(
+            lazy(move || {
+                let _ = started_tx.send(());
+                Ok::<_, ()>(())
+            })
+            .and_then(|()| empty::<(), ()>()),
+        );
+        started_rx.recv_timeout(Duration::from_secs(10)).unwrap();
+
+        handle.abort();
+        assert_eq!(
+            crate::execute(monitor).err(),
+            Some(MonitorError::Failed(AbortableError::Aborted))
+        );
+
+        // Not aborted.
+        let (handle, monitor) = spawn_monitor_abortable(lazy(|| Err::<(), _>("failed")));
+        assert_eq!(
+            crate::execute(monitor).err(),
+            Some(MonitorError::Failed(AbortableError::Failed("failed")))
+        );
+        handle.abort();
+    }
+
+    #[test]
+    fn spawn_scoped_monitor_works() {
+        let monitor = spawn_scoped_monitor(lazy(|| Ok::<_, ()>(1)));
+        assert_eq!(crate::execute(monitor).ok(), Some(1));
+
+        let monitor = spawn_scoped_monitor(lazy(|| Err::<(), _>("failed")));
+        assert_eq!(
+            crate::execute(monitor).err(),
+            Some(MonitorError::Failed("failed"))
+        );
+
+        // Dropping the monitor aborts the fiber.
+        let (tx, rx) = mpsc::channel();
+        let guard = NotifyOnDrop(tx);
+        let monitor = spawn_scoped_monitor(empty::<(), ()>().then(move |r| {
+            let _ = &guard;
+            r
+        }));
+        assert!(rx.recv_timeout(Duration::from_millis(10)).is_err());
+
+        drop(monitor);
+        assert!(rx.recv_timeout(Duration::from_secs(10)).is_ok());
+    }
+}
@@ -0,0 +1,55 @@
+use crate::global;
+use crate::ExecutorHealth;
+
+/// A snapshot of the configuration of the global executor.
+///
+/// New fields may be added in the future.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ExecutorConfig {
+    /// The number of scheduler threads.
+    ///
+    /// If the executor has not started, this is the number that it will use (see [`thread_count`](./fn.thread_count.html)).
+    pub thread_count: usize,
+
+    /// The prefix of the names of the threads (see [`GlobalExecutorBuilder`](./struct.GlobalExecutorBuilder.html)).
+    pub thread_name_prefix: String,
+
+    /// The maximum number of restarts after aborts.
+    pub max_restarts: usize,
+
+    /// Whether the executor has started (see [`is_started`](./fn.is_started.html)).
+    pub started: bool,
+
+    /// The status of the executor (see [`executor_health`](./fn.executor_health.html)).
+    pub health: ExecutorHealth,
+}
+
+/// Returns a snapshot of the configuration of the global executor.
+///
+/// This complements [`metrics`] (the runtime counters) for diagnostics.
+/// The values are the ones the executor actually uses, including the defaults of the unset options.
+/// Unlike `metrics`, this never starts the executor.
+///
+/// [`metrics`]: ./fn.metrics.html
+///
+/// # Examples
+///
+/// ```
+/// let config = fibers_global::config_snapshot();
+/// assert!(!config.started);
+/// assert_eq!(config.thread_name_prefix, "fibers-global");
+/// ```
+pub fn config_snapshot() -> ExecutorConfig {
+    global::with_state(|options, executor| ExecutorConfig {
+        thread_count: executor.map_or_else(global::peek_thread_count, |e| e.thread_count),
+        thread_name_prefix: options.name_prefix.clone(),
+        max_restarts: options.max_restarts,
+        started: executor.is_some(),
+        health: match executor {
+            None => ExecutorHealth::NotStarted,
+            Some(e) if e.is_dead() => ExecutorHealth::Dead,
+            Some(_) => ExecutorHealth::Running,
+        },
+    })
+}
@@ -0,0 +1,11 @@
+/// A value of either of two types.
+///
+/// Unlike `futures::future::Either`, this is not a future and implements the comparison traits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    /// A value of the left type.
+    Left(L),
+
+    /// A value of the right type.
+    Right(R),
+}
@@ -0,0 +1,171 @@
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+
+/// This error is returned when the global executor aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutorAborted;
+impl fmt::Display for ExecutorAborted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The global `ThreadPoolExecutor` aborted")
+    }
+}
+impl Error for ExecutorAborted {}
+
+/// This error is returned when a blocking operation does not complete within the given time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutError;
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Timed out waiting for the fiber")
+    }
+}
+impl Error for TimeoutError {}
+
+/// This error is returned when a future does not complete within the given number of polls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalledError;
+impl fmt::Display for StalledError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The future did not complete within the poll budget")
+    }
+}
+impl Error for StalledError {}
+
+/// This error is returned when the global executor is required to have started but it has not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotStartedError;
+impl fmt::Display for NotStartedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The global `ThreadPoolExecutor` has not started")
+    }
+}
+impl Error for NotStartedError {}
+
+/// This error is returned when the given worker index is not less than the number of scheduler threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidWorkerError {
+    /// The given worker index.
+    pub worker: usize,
+
+    /// The number of scheduler threads of the global executor.
+    pub thread_count: usize,
+}
+impl fmt::Display for InvalidWorkerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The worker index {} is out of range (thread_count={})",
+            self.worker, self.thread_count
+        )
+    }
+}
+impl Error for InvalidWorkerError {}
+
+/// The error type of `checked_spawn`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnError {
+    /// The global executor has aborted and will not be restarted (see `ExecutorHealth::Dead`).
+    ExecutorDead,
+
+    /// The global executor is being drained by `drain_and_shutdown`.
+    Draining,
+}
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpawnError::ExecutorDead => write!(f, "{}", ExecutorAborted),
+            SpawnError::Draining => write!(f, "The global `ThreadPoolExecutor` is being drained"),
+        }
+    }
+}
+impl Error for SpawnError {}
+
+/// The error type of the future returned by `spawn_monitor_timeout`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonitorTimeoutError<E> {
+    /// The monitored fiber failed.
+    Failed(E),
+
+    /// The monitored fiber did not complete within the timeout.
+    Timeout,
+
+    /// The global executor aborted.
+    Aborted,
+}
+impl<E: fmt::Display> fmt::Display for MonitorTimeoutError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MonitorTimeoutError::Failed(e) => write!(f, "The monitored fiber failed: {}", e),
+            MonitorTimeoutError::Timeout => write!(f, "{}", TimeoutError),
+            MonitorTimeoutError::Aborted => write!(f, "{}", ExecutorAborted),
+        }
+    }
+}
+impl<E: Error> Error for MonitorTimeoutError<E> {}
+
+/// The error type of the monitor returned by `spawn_monitor_catch_unwind`.
+#[derive(Debug)]
+pub enum CaughtError<E> {
+    /// The fiber failed.
+    Failed(E),
+
+    /// The fiber panicked with the given payload.
+    Panicked(Box<dyn Any + Send>),
+}
+impl<E: fmt::Display> fmt::Display for CaughtError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaughtError::Failed(e) => write!(f, "The fiber failed: {}", e),
+            CaughtError::Panicked(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()));
+                match message {
+                    Some(message) => write!(f, "The fiber panicked: {}", message),
+                    None => write!(f, "The fiber panicked"),
+                }
+            }
+        }
+    }
+}
+impl<E: Error> Error for CaughtError<E> {}
+
+/// The error type of the monitor returned by `spawn_monitor_abortable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbortableError<E> {
+    /// The fiber failed.
+    Failed(E),
+
+    /// The fiber was aborted via `AbortHandle`.
+    Aborted,
+}
+impl<E: fmt::Display> fmt::Display for AbortableError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AbortableError::Failed(e) => write!(f, "The fiber failed: {}", e),
+            AbortableError::Aborted => write!(f, "The fiber was aborted"),
+        }
+    }
+}
+impl<E: Error> Error for AbortableError<E> {}
+
+/// The error type of `execute_checked`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecuteError<E> {
+    /// The executed future failed.
+    Failed(E),
+
+    /// The global executor aborted.
+    Aborted,
+}
+impl<E: fmt::Display> fmt::Display for ExecuteError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecuteError::Failed(e) => write!(f, "The executed future failed: {}", e),
+            ExecuteError::Aborted => write!(f, "{}", ExecutorAborted),
+        }
+    }
+}
+impl<E: Error> Error for ExecuteError<E> {}
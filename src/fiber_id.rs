@@ -0,0 +1,121 @@
+use fibers::sync::oneshot::Monitor;
+use fibers::Spawn;
+use futures::{Future, Poll};
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{panic_handler, with_spawn_handle};
+
+static NEXT_FIBER_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static CURRENT_FIBER_ID: Cell<Option<FiberId>> = const { Cell::new(None) };
+}
+
+/// The identifier of a fiber spawned by [`spawn_monitor_with_id`]
+/// (or by `spawn` and `spawn_monitor` if the `tracing` feature is enabled).
+///
+/// Identifiers are assigned in increasing order and never reused within a process.
+///
+/// [`spawn_monitor_with_id`]: ./fn.spawn_monitor_with_id.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FiberId(u64);
+impl FiberId {
+    pub(crate) fn next() -> Self {
+        FiberId(NEXT_FIBER_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Returns the value of the identifier.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+impl fmt::Display for FiberId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "fiber#{}", self.0)
+    }
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns its identifier
+/// and a future to monitor it's execution result.
+///
+/// While the fiber is being polled, [`current_fiber_id`] returns the identifier,
+/// so the handler set by [`set_fiber_panic_handler`] can include it in panic reports.
+/// If the `tracing` feature is enabled, the identifier is also the `id` field of the `fiber` span of the fiber.
+///
+/// [`current_fiber_id`]: ./fn.current_fiber_id.html
+/// [`set_fiber_panic_handler`]: ./fn.set_fiber_panic_handler.html
+pub fn spawn_monitor_with_id<F>(future: F) -> (FiberId, Monitor<F::Item, F::Error>)
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let id = FiberId::next();
+    let future = panic_handler::wrap(future);
+    #[cfg(feature = "tracing")]
+    let future = crate::trace::instrument(id, future);
+    #[cfg(not(feature = "tracing"))]
+    let future = WithFiberId::new(id, future);
+    let monitor = with_spawn_handle(future, |h, f| h.spawn_monitor(f));
+    (id, monitor)
+}
+
+/// Returns the identifier of the fiber being polled on the current thread
+/// if it has been spawned by [`spawn_monitor_with_id`] (or by `spawn` and the like with the `tracing` feature).
+///
+/// [`spawn_monitor_with_id`]: ./fn.spawn_monitor_with_id.html
+pub fn current_fiber_id() -> Option<FiberId> {
+    CURRENT_FIBER_ID.with(|id| id.get())
+}
+
+/// A future that sets `CURRENT_FIBER_ID` while it is polled.
+pub(crate) struct WithFiberId<F> {
+    id: FiberId,
+    future: F,
+}
+impl<F> WithFiberId<F> {
+    pub(crate) fn new(id: FiberId, future: F) -> Self {
+        WithFiberId { id, future }
+    }
+}
+impl<F: Future> Future for WithFiberId<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        struct Reset(Option<FiberId>);
+        impl Drop for Reset {
+            fn drop(&mut self) {
+                CURRENT_FIBER_ID.with(|id| id.set(self.0));
+            }
+        }
+
+        let _reset = Reset(CURRENT_FIBER_ID.with(|id| id.replace(Some(self.id))));
+        self.future.poll()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::lazy;
+
+    use super::*;
+
+    #[test]
+    fn spawn_monitor_with_id_works() {
+        let (id0, monitor0) = spawn_monitor_with_id(lazy(|| Ok::<_, ()>(current_fiber_id())));
+        let (id1, monitor1) = spawn_monitor_with_id(lazy(|| Ok::<_, ()>(current_fiber_id())));
+        assert!(id0 < id1);
+        assert_eq!(crate::execute(monitor0).ok(), Some(Some(id0)));
+        assert_eq!(crate::execute(monitor1).ok(), Some(Some(id1)));
+        assert_eq!(current_fiber_id(), None);
+
+        let ids = (0..100)
+            .map(|_| spawn_monitor_with_id(futures::finished::<(), ()>(())).0)
+            .collect::<Vec<_>>();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(id0.to_string(), format!("fiber#{}", id0.as_u64()));
+    }
+}
@@ -0,0 +1,702 @@
+use fibers::executor::ThreadPoolExecutorHandle;
+use fibers::sync::{mpsc, oneshot};
+use fibers::{Executor, Spawn, ThreadPoolExecutor};
+use futures::{Async, Future, Poll, Stream};
+#[cfg(not(feature = "no-threads"))]
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+
+use crate::GlobalExecutorBuilder;
+
+/// The default prefix of the names of the threads spawned by the global executor.
+pub(crate) const DEFAULT_THREAD_NAME_PREFIX: &str = "fibers-global";
+
+/// The thread count has not been set (i.e., the default value will be used).
+const UNSET: u8 = 0;
+
+/// The thread count has been set explicitly.
+const SET: u8 = 1;
+
+/// The executor has started with the thread count, so it can no longer be changed.
+const STARTED: u8 = 2;
+
+/// The thread count is being written by `store_thread_count`.
+const WRITING: u8 = 3;
+
+static THREAD_COUNT_STATE: AtomicU8 = AtomicU8::new(UNSET);
+static THREAD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Moves `THREAD_COUNT_STATE` from `UNSET` or `SET` to `next` and returns the previous state.
+///
+/// If the state is `STARTED`, `None` is returned. If it is `WRITING`, this waits until the writer finishes.
+fn transit_thread_count_state(next: u8) -> Option<u8> {
+    let mut current = THREAD_COUNT_STATE.load(Ordering::SeqCst);
+    loop {
+        match current {
+            STARTED => return None,
+            WRITING => {
+                std::hint::spin_loop();
+                current = THREAD_COUNT_STATE.load(Ordering::SeqCst);
+            }
+            _ => match THREAD_COUNT_STATE.compare_exchange_weak(
+                current,
+                next,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(previous) => return Some(previous),
+                Err(actual) => current = actual,
+            },
+        }
+    }
+}
+
+/// Fixes the thread count and returns it.
+fn get_thread_count() -> usize {
+    match transit_thread_count_state(STARTED) {
+        Some(UNSET) => {
+            let n = default_thread_count();
+            THREAD_COUNT.store(n, Ordering::SeqCst);
+            n
+        }
+        _ => THREAD_COUNT.load(Ordering::SeqCst),
+    }
+}
+
+/// Returns the thread count that the global executor will use (or is using), without fixing it.
+pub(crate) fn peek_thread_count() -> usize {
+    loop {
+        match THREAD_COUNT_STATE.load(Ordering::SeqCst) {
+            UNSET => return default_thread_count(),
+            WRITING => std::hint::spin_loop(),
+            _ => return THREAD_COUNT.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// The environment variable to override the default thread count.
+const THREADS_ENV: &str = "FIBERS_GLOBAL_THREADS";
+
+/// Returns the number of threads used if no thread count is set explicitly.
+///
+/// If `FIBERS_GLOBAL_THREADS` is set to a positive integer, it is used.
+/// Otherwise this is the number of physical CPUs capped by the available parallelism of the process,
+/// which reflects cgroup quotas and CPU affinity masks (`num_cpus::get()` may report the number of the host CPUs).
+pub(crate) fn default_thread_count() -> usize {
+    if let Some(n) = env_thread_count() {
+        return n;
+    }
+    match thread::available_parallelism() {
+        Ok(n) => std::cmp::min(num_cpus::get_physical(), n.get()),
+        Err(_) => num_cpus::get(),
+    }
+}
+
+/// Clamps `n` to `MAX_THREAD_COUNT` with a warning.
+pub(crate) fn clamp_thread_count(n: usize) -> usize {
+    if n <= crate::MAX_THREAD_COUNT {
+        return n;
+    }
+    report_warning(format_args!(
+        "The thread count {} of the global executor is clamped to {}",
+        n,
+        crate::MAX_THREAD_COUNT
+    ));
+    crate::MAX_THREAD_COUNT
+}
+
+/// Parses `FIBERS_GLOBAL_THREADS` (a malformed value is warned and ignored).
+fn env_thread_count() -> Option<usize> {
+    let value = std::env::var_os(THREADS_ENV)?;
+    match value.to_str().and_then(|v| v.trim().parse().ok()) {
+        Some(0) | None => {
+            report_warning(format_args!(
+                "Ignored the malformed `{}` value: {:?} (expected a positive integer)",
+                THREADS_ENV, value
+            ));
+            None
+        }
+        Some(n) => Some(clamp_thread_count(n)),
+    }
+}
+
+fn store_thread_count(n: usize) -> bool {
+    if transit_thread_count_state(WRITING).is_none() {
+        return false;
+    }
+    THREAD_COUNT.store(n, Ordering::SeqCst);
+    THREAD_COUNT_STATE.store(SET, Ordering::SeqCst);
+    true
+}
+
+/// Whether the global executor is running.
+///
+/// This flag is updated while holding the lock of `GLOBAL_STATE`,
+/// but can be read without it.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Incremented whenever the handle of the global executor changes or becomes unusable
+/// (i.e., the executor starts, restarts, aborts or stops), which invalidates the handles cached by threads.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+fn bump_generation() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Options of the global executor other than the thread count.
+#[derive(Debug, Clone)]
+pub(crate) struct ExecutorOptions {
+    pub name_prefix: String,
+    pub max_restarts: usize,
+}
+impl Default for ExecutorOptions {
+    fn default() -> Self {
+        ExecutorOptions {
+            name_prefix: DEFAULT_THREAD_NAME_PREFIX.to_owned(),
+            max_restarts: 0,
+        }
+    }
+}
+
+struct GlobalState {
+    executor: Option<GlobalExecutor>,
+    config: ExecutorOptions,
+}
+
+lazy_static! {
+    static ref GLOBAL_STATE: Mutex<GlobalState> = Mutex::new(GlobalState {
+        executor: None,
+        config: ExecutorOptions::default(),
+    });
+}
+
+fn lock_state() -> MutexGuard<'static, GlobalState> {
+    GLOBAL_STATE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Calls `f` with the global executor, starting it if needed.
+pub(crate) fn with_global_executor<F, T>(f: F) -> T
+where
+    F: FnOnce(&GlobalExecutor) -> T,
+{
+    let mut state = lock_state();
+    if state.executor.is_none() {
+        let thread_count = get_thread_count();
+        state.executor = Some(GlobalExecutor::start(thread_count, &state.config));
+        RUNNING.store(true, Ordering::SeqCst);
+        bump_generation();
+    }
+    f(state.executor.as_ref().expect("Never fails"))
+}
+
+#[cfg(not(feature = "no-threads"))]
+thread_local! {
+    static CACHED_HANDLE: RefCell<Option<CachedHandle>> = const { RefCell::new(None) };
+}
+
+/// A clone of the handle of the global executor cached by a thread (see `cached_handle`).
+#[cfg(not(feature = "no-threads"))]
+#[derive(Clone)]
+pub(crate) struct CachedHandle {
+    generation: usize,
+    pub handle: ThreadPoolExecutorHandle,
+    pub dead: bool,
+}
+
+/// Returns a clone of the handle of the global executor, starting it if needed.
+///
+/// The handle is cached by the current thread, so `GLOBAL_STATE` is locked only if
+/// the executor has started, restarted, aborted or stopped since the last call on this thread.
+#[cfg(not(feature = "no-threads"))]
+pub(crate) fn cached_handle() -> CachedHandle {
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let cached = CACHED_HANDLE
+        .try_with(|cached| {
+            cached
+                .borrow()
+                .as_ref()
+                .filter(|cached| cached.generation == generation)
+                .cloned()
+        })
+        .ok()
+        .flatten();
+    if let Some(cached) = cached {
+        return cached;
+    }
+
+    // `dead` is set before the generation is bumped, so it must be loaded after the generation.
+    let cached = with_global_executor(|executor| CachedHandle {
+        generation: GENERATION.load(Ordering::SeqCst),
+        handle: executor.handle.clone(),
+        dead: executor.is_dead(),
+    });
+    let _ = CACHED_HANDLE.try_with(|c| *c.borrow_mut() = Some(cached.clone()));
+    cached
+}
+
+/// Calls `f` with the options and the running instance (if any) of the global executor, without starting it.
+pub(crate) fn with_state<F, T>(f: F) -> T
+where
+    F: FnOnce(&ExecutorOptions, Option<&GlobalExecutor>) -> T,
+{
+    let state = lock_state();
+    f(&state.config, state.executor.as_ref())
+}
+
+/// Calls `f` with the global executor if it is running, without starting it.
+pub(crate) fn with_running_executor<F, T>(f: F) -> Option<T>
+where
+    F: FnOnce(&GlobalExecutor) -> T,
+{
+    lock_state().executor.as_ref().map(f)
+}
+
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether `drain_and_shutdown` is in progress (i.e., `spawn` should not accept new fibers).
+pub(crate) fn set_draining(draining: bool) {
+    DRAINING.store(draining, Ordering::SeqCst);
+}
+
+/// Returns `true` if `drain_and_shutdown` is in progress.
+pub(crate) fn is_draining() -> bool {
+    DRAINING.load(Ordering::SeqCst)
+}
+
+/// Returns `true` if the global executor is running.
+pub(crate) fn is_started() -> bool {
+    RUNNING.load(Ordering::SeqCst)
+}
+
+/// Returns `true` if the running global executor has aborted (and will not be restarted).
+pub(crate) fn is_dead() -> bool {
+    lock_state()
+        .executor
+        .as_ref()
+        .is_some_and(GlobalExecutor::is_dead)
+}
+
+/// Applies the options set in `builder` if the global executor has never started.
+pub(crate) fn configure(builder: &GlobalExecutorBuilder) -> bool {
+    let mut state = lock_state();
+    if THREAD_COUNT_STATE.load(Ordering::SeqCst) == STARTED {
+        return false;
+    }
+    if let Some(n) = builder.thread_count {
+        if !store_thread_count(n) {
+            return false;
+        }
+    }
+    if let Some(ref prefix) = builder.thread_name_prefix {
+        state.config.name_prefix = prefix.clone();
+    }
+    if let Some(n) = builder.max_restarts {
+        state.config.max_restarts = n;
+    }
+    true
+}
+
+/// Changes the thread count even if it has been fixed by a start of the executor, which must be stopped.
+///
+/// If `start` is `true`, a new global executor is started with the count.
+/// Returns `false` (doing nothing) if an executor has been started by another thread in the meantime.
+pub(crate) fn force_thread_count(n: usize, start: bool) -> bool {
+    let mut state = lock_state();
+    if state.executor.is_some() {
+        return false;
+    }
+    let n = clamp_thread_count(n);
+    THREAD_COUNT.store(n, Ordering::SeqCst);
+    THREAD_COUNT_STATE.store(STARTED, Ordering::SeqCst);
+    if start {
+        state.executor = Some(GlobalExecutor::start(n, &state.config));
+        RUNNING.store(true, Ordering::SeqCst);
+        bump_generation();
+    }
+    true
+}
+
+/// Stops the global executor if it is running.
+pub(crate) fn stop() {
+    let executor = {
+        let mut state = lock_state();
+        RUNNING.store(false, Ordering::SeqCst);
+        bump_generation();
+        state.executor.take()
+    };
+    if let Some(executor) = executor {
+        executor.stop();
+    }
+}
+
+/// Stops the global executor and restores the initial state (i.e., the default configuration).
+#[cfg(feature = "test-reset")]
+pub(crate) fn reset() {
+    let executor = {
+        let mut state = lock_state();
+        RUNNING.store(false, Ordering::SeqCst);
+        state.config = ExecutorOptions::default();
+        THREAD_COUNT_STATE.store(UNSET, Ordering::SeqCst);
+        bump_generation();
+        state.executor.take()
+    };
+    if let Some(executor) = executor {
+        executor.stop();
+    }
+}
+
+/// Creates a `ThreadPoolExecutor` in the same way as the global executor.
+pub(crate) fn new_executor(thread_count: usize) -> io::Result<ThreadPoolExecutor> {
+    ThreadPoolExecutor::with_thread_count(thread_count)
+}
+
+/// A future pinned to a scheduler thread by `spawn_on`.
+pub(crate) type PinnedFuture = Box<dyn Future<Item = (), Error = ()> + Send>;
+
+/// The native thread ids of the scheduler threads, recorded by the sentinels (see `spawn_sentinels`).
+pub(crate) type ThreadIds = Arc<Mutex<Vec<Option<u64>>>>;
+
+/// A running instance of the global executor.
+pub(crate) struct GlobalExecutor {
+    pub handle: ThreadPoolExecutorHandle,
+    pub thread_count: usize,
+
+    /// The senders to the sentinel fibers (see `spawn_sentinels`), indexed by the scheduler threads.
+    pub workers: Vec<mpsc::Sender<PinnedFuture>>,
+
+    /// Whether all the scheduler threads have been confirmed to be running (see `warm_up`).
+    pub warmed_up: AtomicBool,
+    pub thread_ids: ThreadIds,
+    stop: Arc<AtomicBool>,
+    dead: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+impl GlobalExecutor {
+    fn start(thread_count: usize, config: &ExecutorOptions) -> Self {
+        let executor = new_executor(thread_count).unwrap_or_else(|e| {
+            panic!(
+                "Cannot create the global `ThreadPoolExecutor` (thread_count={}): {}",
+                thread_count, e
+            )
+        });
+        let handle = executor.handle();
+        let thread_ids = ThreadIds::default();
+        let (sentinels, workers) = spawn_sentinels(
+            &handle,
+            thread_count,
+            &config.name_prefix,
+            &thread_ids,
+            true,
+        );
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let dead = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            let dead = Arc::clone(&dead);
+            let config = config.clone();
+            let thread_ids = Arc::clone(&thread_ids);
+            let builder = thread::Builder::new().name(format!("{}-run", config.name_prefix));
+            spawn_thread(builder, move || {
+                run(
+                    executor,
+                    sentinels,
+                    thread_count,
+                    &config,
+                    &stop,
+                    &dead,
+                    &thread_ids,
+                )
+            })
+            .unwrap_or_else(|e| {
+                panic!(
+                    "Cannot spawn the background thread of the global executor (thread_count={}): {}",
+                    thread_count, e
+                )
+            })
+        };
+        GlobalExecutor {
+            handle,
+            thread_count,
+            workers,
+            warmed_up: AtomicBool::new(false),
+            thread_ids,
+            stop,
+            dead,
+            thread,
+        }
+    }
+
+    /// Returns `true` if the executor has aborted (and will not be restarted).
+    pub fn is_dead(&self) -> bool {
+        self.dead.load(Ordering::SeqCst)
+    }
+
+    /// Stops the executor and waits until the background thread exits.
+    ///
+    /// The scheduler threads exit asynchronously after this returns,
+    /// dropping the fibers that were remaining on them.
+    fn stop(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = self.thread.join();
+    }
+}
+
+#[cfg(feature = "test-fault-injection")]
+static INJECT_THREAD_SPAWN_FAILURE: AtomicBool = AtomicBool::new(false);
+
+/// Makes the spawn of the background thread fail (for testing).
+#[cfg(feature = "test-fault-injection")]
+pub(crate) fn inject_thread_spawn_failure(fail: bool) {
+    INJECT_THREAD_SPAWN_FAILURE.store(fail, Ordering::SeqCst);
+}
+
+fn spawn_thread<F>(builder: thread::Builder, f: F) -> io::Result<JoinHandle<()>>
+where
+    F: FnOnce() + Send + 'static,
+{
+    #[cfg(feature = "test-fault-injection")]
+    {
+        if INJECT_THREAD_SPAWN_FAILURE.load(Ordering::SeqCst) {
+            return Err(io::Error::other("injected failure"));
+        }
+    }
+    builder.spawn(f)
+}
+
+/// Runs the global executor until `stop` is set.
+///
+/// If the executor aborts (e.g., a fiber panicked), it is rebuilt up to `config.max_restarts` times.
+/// The fibers remaining on the aborted executor are dropped.
+fn run(
+    mut executor: ThreadPoolExecutor,
+    mut sentinels: Vec<oneshot::Sender<()>>,
+    thread_count: usize,
+    config: &ExecutorOptions,
+    stop: &Arc<AtomicBool>,
+    dead: &AtomicBool,
+    thread_ids: &ThreadIds,
+) {
+    let mut restarts = 0;
+    while !stop.load(Ordering::SeqCst) {
+        let e = match executor.run_once() {
+            Ok(()) => continue,
+            Err(e) => e,
+        };
+        if restarts == config.max_restarts {
+            report_error(format_args!(
+                "The global `ThreadPoolExecutor` aborted: {}",
+                e
+            ));
+            dead.store(true, Ordering::SeqCst);
+            bump_generation();
+            break;
+        }
+        restarts += 1;
+        report_error(format_args!(
+            "The global `ThreadPoolExecutor` aborted: {} (restarting {}/{})",
+            e, restarts, config.max_restarts
+        ));
+        match new_executor(thread_count) {
+            Err(e) => {
+                report_error(format_args!(
+                    "Cannot restart the global `ThreadPoolExecutor`: {}",
+                    e
+                ));
+                dead.store(true, Ordering::SeqCst);
+                bump_generation();
+                break;
+            }
+            Ok(new_executor) => {
+                // The old executor is dropped before its sentinels (see `spawn_sentinels`).
+                executor = new_executor;
+                let (new_sentinels, workers) = spawn_sentinels(
+                    &executor.handle(),
+                    thread_count,
+                    &config.name_prefix,
+                    thread_ids,
+                    true,
+                );
+                sentinels = new_sentinels;
+                replace_handle(stop, executor.handle(), workers);
+            }
+        }
+    }
+    drop(executor);
+    drop(sentinels);
+}
+
+/// Reports an error of the background thread (or of a fiber).
+///
+/// The error is logged at the error level if the `log` feature is enabled, otherwise printed to the standard error.
+pub(crate) fn report_error(message: fmt::Arguments) {
+    #[cfg(feature = "log")]
+    log::error!("{}", message);
+    #[cfg(not(feature = "log"))]
+    eprintln!("{}", message);
+}
+
+/// Reports a misuse of this crate.
+///
+/// The message is logged at the warning level if the `log` feature is enabled, otherwise printed to the standard error.
+pub(crate) fn report_warning(message: fmt::Arguments) {
+    #[cfg(feature = "log")]
+    log::warn!("{}", message);
+    #[cfg(not(feature = "log"))]
+    eprintln!("{}", message);
+}
+
+/// Replaces the handles of the global executor identified by `stop` (if it is still the current one).
+fn replace_handle(
+    stop: &Arc<AtomicBool>,
+    handle: ThreadPoolExecutorHandle,
+    workers: Vec<mpsc::Sender<PinnedFuture>>,
+) {
+    let mut state = lock_state();
+    if let Some(executor) = state.executor.as_mut() {
+        if Arc::ptr_eq(&executor.stop, stop) {
+            executor.handle = handle;
+            executor.workers = workers;
+            executor.warmed_up.store(false, Ordering::SeqCst);
+            bump_generation();
+        }
+    }
+}
+
+/// Parks one sentinel fiber on each scheduler thread.
+///
+/// Since the executor assigns fibers to the schedulers in round robin,
+/// the first `thread_count` fibers cover all of them.
+///
+/// An idle scheduler thread blocks until it receives a request,
+/// so they are woken up by dropping the returned stop senders after the executor has been dropped.
+///
+/// The sentinels also run the futures sent via the returned worker senders on their threads,
+/// and record the native ids of their threads into `thread_ids` (which is cleared first).
+/// If `pool_threads` is `true`, they also mark their threads as the ones of the global executor (see `on_pool_thread`).
+#[allow(clippy::type_complexity)]
+pub(crate) fn spawn_sentinels(
+    handle: &ThreadPoolExecutorHandle,
+    thread_count: usize,
+    name_prefix: &str,
+    thread_ids: &ThreadIds,
+    pool_threads: bool,
+) -> (Vec<oneshot::Sender<()>>, Vec<mpsc::Sender<PinnedFuture>>) {
+    *thread_ids.lock().unwrap_or_else(PoisonError::into_inner) = vec![None; thread_count];
+    (0..thread_count)
+        .map(|i| {
+            let (stop_tx, stop_rx) = oneshot::channel();
+            let (pinned_tx, pinned_rx) = mpsc::channel();
+            handle.spawn(Sentinel {
+                thread_name: Some(format!("{}-{}", name_prefix, i)),
+                thread_id: Some((i, Arc::clone(thread_ids))),
+                pool_thread: pool_threads,
+                stopped: stop_rx,
+                incoming: pinned_rx,
+                pinned: Vec::new(),
+            });
+            (stop_tx, pinned_tx)
+        })
+        .unzip()
+}
+
+/// A fiber that resides on a scheduler thread until the executor stops.
+///
+/// The pinned futures are polled as a part of this fiber, so all of them are re-polled whenever one is woken up.
+struct Sentinel {
+    thread_name: Option<String>,
+    thread_id: Option<(usize, ThreadIds)>,
+    pool_thread: bool,
+    stopped: oneshot::Receiver<()>,
+    incoming: mpsc::Receiver<PinnedFuture>,
+    pinned: Vec<PinnedFuture>,
+}
+impl Future for Sentinel {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(name) = self.thread_name.take() {
+            set_os_thread_name(&name);
+            if self.pool_thread {
+                crate::worker::mark_pool_thread();
+            }
+        }
+        if let Some((i, thread_ids)) = self.thread_id.take() {
+            let mut thread_ids = thread_ids.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(id) = thread_ids.get_mut(i) {
+                *id = Some(native_thread_id());
+            }
+        }
+        match self.stopped.poll() {
+            Ok(Async::NotReady) => {}
+            _ => return Ok(Async::Ready(())),
+        }
+        while let Ok(Async::Ready(Some(future))) = self.incoming.poll() {
+            self.pinned.push(future);
+        }
+        self.pinned
+            .retain_mut(|future| matches!(future.poll(), Ok(Async::NotReady)));
+        Ok(Async::NotReady)
+    }
+}
+
+/// Sets the name of the current thread seen by the OS (e.g., `top -H` and debuggers).
+///
+/// The scheduler threads are spawned inside `fibers` without names,
+/// so this is the only way to name them. Note that `std::thread::current().name()` is not affected.
+#[cfg(target_os = "linux")]
+fn set_os_thread_name(name: &str) {
+    // Linux limits the length of a thread name to 15 bytes.
+    let mut len = std::cmp::min(name.len(), 15);
+    while !name.is_char_boundary(len) {
+        len -= 1;
+    }
+    if let Ok(name) = std::ffi::CString::new(&name[..len]) {
+        unsafe {
+            libc::pthread_setname_np(libc::pthread_self(), name.as_ptr());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_os_thread_name(_name: &str) {}
+
+/// Returns the id of the current thread assigned by the OS (i.e., `gettid(2)` on Linux).
+#[cfg(target_os = "linux")]
+pub(crate) fn native_thread_id() -> u64 {
+    unsafe { libc::syscall(libc::SYS_gettid) as u64 }
+}
+
+/// Returns an opaque id of the current thread derived from `std::thread::ThreadId`.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn native_thread_id() -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    thread::current().id().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(all(test, target_os = "linux", not(feature = "no-threads")))]
+pub(crate) mod tests {
+    pub(crate) fn os_thread_name() -> String {
+        let mut buf = [0u8; 16];
+        unsafe {
+            libc::pthread_getname_np(libc::pthread_self(), buf.as_mut_ptr() as *mut _, buf.len());
+        }
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        String::from_utf8_lossy(&buf[..len]).into_owned()
+    }
+
+    #[test]
+    fn scheduler_threads_are_named() {
+        let name = crate::execute(futures::lazy(|| Ok::<_, ()>(os_thread_name()))).unwrap();
+        assert!(name.starts_with("fibers-global-"), "{}", name);
+    }
+}
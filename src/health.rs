@@ -0,0 +1,31 @@
+use crate::global;
+
+/// The status of the global executor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExecutorHealth {
+    /// The global executor has not started yet (or has been shut down).
+    NotStarted,
+
+    /// The global executor is running.
+    Running,
+
+    /// The global executor has aborted and will not be restarted.
+    ///
+    /// Fibers spawned in this state are dropped without being executed.
+    /// Calling `shutdown` lets the next use start a fresh executor.
+    Dead,
+}
+
+/// Returns the status of the global executor.
+///
+/// Like `is_started`, this never starts the executor,
+/// so it can be used as a cheap liveness probe.
+pub fn executor_health() -> ExecutorHealth {
+    if !global::is_started() {
+        ExecutorHealth::NotStarted
+    } else if global::is_dead() {
+        ExecutorHealth::Dead
+    } else {
+        ExecutorHealth::Running
+    }
+}
@@ -0,0 +1,149 @@
+use fibers::sync::oneshot;
+use fibers::time::timer::{self, Timeout};
+use futures::{Async, Future, Poll};
+use std::time::{Duration, Instant};
+
+/// Spawns a fiber that calls `f` every `period` by using the global `ThreadPoolExecutor`.
+///
+/// The first call happens `period` after the invocation of this function.
+/// The fiber keeps running until the returned handle is dropped.
+///
+/// If a call of `f` takes longer than `period`, the ticks missed during the call are skipped
+/// (i.e., they are not piled up) and the next call happens at the next tick in the original schedule.
+///
+/// Since `f` is called on a worker thread of the executor, it should not block for a long time.
+///
+/// # Panics
+///
+/// If `period` is zero, the current thread will panic.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::mpsc;
+/// use std::time::Duration;
+///
+/// # if cfg!(feature = "no-threads") { return; }
+/// let (tx, rx) = mpsc::channel();
+/// let handle = fibers_global::spawn_interval(Duration::from_millis(1), move || {
+///     let _ = tx.send(());
+/// });
+/// for _ in 0..3 {
+///     rx.recv().unwrap();
+/// }
+/// drop(handle);
+/// ```
+pub fn spawn_interval<F>(period: Duration, f: F) -> IntervalHandle
+where
+    F: FnMut() + Send + 'static,
+{
+    assert_ne!(period, Duration::from_secs(0));
+    let (tx, rx) = oneshot::channel();
+    let next = Instant::now() + period;
+    crate::spawn(Interval {
+        period,
+        next,
+        timer: timer::timeout(period),
+        f,
+        stopped: rx,
+    });
+    IntervalHandle { _stop: tx }
+}
+
+/// A handle of the fiber spawned by [`spawn_interval`].
+///
+/// Dropping this stops the fiber.
+///
+/// [`spawn_interval`]: ./fn.spawn_interval.html
+#[derive(Debug)]
+pub struct IntervalHandle {
+    _stop: oneshot::Sender<()>,
+}
+
+struct Interval<F> {
+    period: Duration,
+    next: Instant,
+    timer: Timeout,
+    f: F,
+    stopped: oneshot::Receiver<()>,
+}
+impl<F: FnMut()> Future for Interval<F> {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            match self.stopped.poll() {
+                Ok(Async::NotReady) => {}
+                _ => return Ok(Async::Ready(())),
+            }
+            match self.timer.poll() {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(())) => {}
+                Err(_) => return Ok(Async::Ready(())),
+            }
+
+            (self.f)();
+
+            let now = Instant::now();
+            self.next += self.period;
+            while self.next <= now {
+                // Skips the missed ticks.
+                self.next += self.period;
+            }
+            self.timer = timer::timeout(self.next - now);
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "no-threads")))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn spawn_interval_works() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let handle = {
+            let count = Arc::clone(&count);
+            spawn_interval(Duration::from_millis(1), move || {
+                count.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while count.load(Ordering::SeqCst) < 3 {
+            assert!(Instant::now() < deadline, "the interval did not tick");
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        drop(handle);
+        thread::sleep(Duration::from_millis(20));
+        let stopped = count.load(Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(count.load(Ordering::SeqCst), stopped);
+    }
+
+    #[test]
+    fn spawn_interval_skips_missed_ticks() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let handle = {
+            let count = Arc::clone(&count);
+            spawn_interval(Duration::from_millis(2), move || {
+                count.fetch_add(1, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(20));
+            })
+        };
+        thread::sleep(Duration::from_millis(100));
+        drop(handle);
+
+        // Ticks are not piled up, so `f` is called at most once per its own duration.
+        assert!(
+            count.load(Ordering::SeqCst) <= 6,
+            "{}",
+            count.load(Ordering::SeqCst)
+        );
+    }
+}
@@ -28,116 +28,1657 @@
 //! }));
 //!
 //! // Executes a calculation that depends on the above fibers.
-//! let result = fibers_global::execute(rx0.join(rx1).map(|(v0, v1)| v0 + v1));
-//! assert_eq!(result.ok(), Some(3));
+//! let (v0, v1) = fibers_global::execute_join2(rx0, rx1).unwrap();
+//! assert_eq!(v0 + v1, 3);
 //! # }
 //! ```
+//!
+//! # The `no-threads` feature
+//!
+//! If the `no-threads` feature is enabled, this crate never spawns threads (e.g., for environments with
+//! thread restrictions). Instead, the fibers are spawned on a thread-local `fibers::InPlaceExecutor`,
+//! which is run by the calling thread only while it waits in the functions of this crate
+//! (e.g., `execute`, `wait_for_idle` or the futures returned by `into_global`). Thus:
+//!
+//! - The fibers spawned by a thread make progress only while that thread waits,
+//!   and only on that thread. Blocking by other means (e.g., `std::sync::mpsc::Receiver::recv`) does not run them.
+//! - Waiting inside a fiber is impossible (see `execute`).
+//! - A panic of a fiber drops all the fibers of the thread-local executor and is resumed on the waiting thread.
+//! - The functions exposing the `ThreadPoolExecutor` (`handle`, `with_handle` and `build_executor`) are not available,
+//!   and `thread_count` returns `1`.
 #![warn(missing_docs)]
 #[macro_use]
 extern crate lazy_static;
 
-use fibers::executor::ThreadPoolExecutorHandle;
-use fibers::sync::oneshot::{Monitor, MonitorError};
-use fibers::Spawn;
-use futures::{Async, Future};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Duration;
+use fibers::executor::ThreadPoolExecutorHandle;
+use fibers::sync::oneshot::{self, Monitor, MonitorError};
+use fibers::time::timer;
+use fibers::{Executor, InPlaceExecutor, Spawn, ThreadPoolExecutor};
+use futures::future::{Either as FutureEither, Shared};
+use futures::{Async, Future, IntoFuture};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::panic::AssertUnwindSafe;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub use batch::{
+    execute_all, execute_all_limited, execute_all_settled, execute_batch_unordered, execute_join2,
+    execute_join3, execute_keyed, execute_select2, join_monitors, spawn_all,
+};
+pub use budget::execute_with_budget;
+pub use builder::GlobalExecutorBuilder;
+pub use cancel::{
+    spawn_cancellable, spawn_monitor_abortable, spawn_scoped_monitor, AbortHandle, CancelHandle,
+    ScopedMonitor,
+};
+pub use config::{config_snapshot, ExecutorConfig};
+pub use either::Either;
+pub use error::{
+    AbortableError, CaughtError, ExecuteError, ExecutorAborted, InvalidWorkerError,
+    MonitorTimeoutError, NotStartedError, SpawnError, StalledError, TimeoutError,
+};
+pub use fiber_id::{current_fiber_id, spawn_monitor_with_id, FiberId};
+pub use health::{executor_health, ExecutorHealth};
+pub use interval::{spawn_interval, IntervalHandle};
+pub use metrics::{
+    blocking_callers, in_flight_fibers, metrics, queue_depth, spawned_count, wait_for_idle,
+    GlobalMetrics,
+};
+pub use monitor::{poll_monitor_once, spawn_and_poll_once};
+pub use panic_handler::set_fiber_panic_handler;
+pub use retry::{execute_retry, RetryPolicy};
+pub use scope::{scope, Scope};
+pub use shutdown::{install_atexit_drain, ShutdownGuard};
+#[cfg(feature = "std-future")]
+pub use std_future::{execute_std, spawn_monitor_std, spawn_std};
+pub use stream::{execute_stream, spawn_stream, spawn_with_channel};
+pub use wait_policy::{execute_with_policy, WaitPolicy};
+pub use worker::{on_pool_thread, spawn_on, warm_up, worker_thread_ids};
+
+use global::with_global_executor;
+use metrics::BlockingGuard;
+use monitor::TaskMonitor;
+use waiter::Waiter;
+
+mod batch;
+mod budget;
+mod builder;
+mod cancel;
+mod config;
+mod either;
+mod error;
+mod fiber_id;
+mod global;
+mod health;
+mod interval;
+mod metrics;
+mod monitor;
+#[cfg(feature = "no-threads")]
+mod no_threads;
+mod panic_handler;
+mod retry;
+mod scope;
+mod shutdown;
+#[cfg(feature = "std-future")]
+mod std_future;
+mod stream;
+#[cfg(feature = "tracing")]
+mod trace;
+mod wait_policy;
+mod waiter;
+mod worker;
+
+/// The maximum number of scheduler threads of the global executor.
+///
+/// Larger thread counts are clamped to this value (with a warning).
+pub const MAX_THREAD_COUNT: usize = 1024;
+
+/// Sets the number of scheduler threads used by the global executor.
+///
+/// If the global executor already has started,
+/// the invocation of this function has no effect and `false` is returned.
+///
+/// This is equivalent to `GlobalExecutorBuilder::new().thread_count(n).try_init()`.
+///
+/// If this function is never called, the thread count is taken from the `FIBERS_GLOBAL_THREADS`
+/// environment variable (if it is set to a positive integer; other values are ignored with a warning),
+/// or is the number of available physical CPUs by default.
+///
+/// A count greater than [`MAX_THREAD_COUNT`] (e.g., `usize::MAX`) is clamped to it with a warning.
+///
+/// [`MAX_THREAD_COUNT`]: ./constant.MAX_THREAD_COUNT.html
+///
+/// # Panics
+///
+/// If the specified count is `0`, the current thread will panic.
+pub fn set_thread_count(n: usize) -> bool {
+    GlobalExecutorBuilder::new().thread_count(n).try_init()
+}
+
+/// Sets the number of scheduler threads used by the global executor, rebuilding the executor if it has started.
+///
+/// If the thread count has not been fixed yet, this is the same as [`set_thread_count`] and `false` is returned.
+/// Otherwise, if the executor is running, it is stopped by [`drain_and_shutdown`] (waiting at most 10 seconds
+/// for the in-flight fibers) and a new one is started with `n` threads, and then `true` is returned.
+/// If the executor has been shut down, `n` is used at its next start and `false` is returned.
+///
+/// # Hazards
+///
+/// This is a heavyweight operation intended for applications that cannot configure the executor earlier.
+/// While draining, new fibers are not accepted (see [`drain_and_shutdown`]), and the fibers still in flight
+/// after the timeout are dropped. Handles obtained before the rebuild keep referring to the stopped executor,
+/// and code relying on the invariant that [`thread_count`] never changes may observe inconsistent values.
+///
+/// # Panics
+///
+/// If the specified count is `0`, the current thread will panic.
+///
+/// [`set_thread_count`]: ./fn.set_thread_count.html
+/// [`drain_and_shutdown`]: ./fn.drain_and_shutdown.html
+/// [`thread_count`]: ./fn.thread_count.html
+pub fn set_thread_count_forced(n: usize) -> bool {
+    const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+    if set_thread_count(n) {
+        return false;
+    }
+    loop {
+        let running = is_started();
+        if running {
+            drain_and_shutdown(DRAIN_TIMEOUT);
+        }
+        if global::force_thread_count(n, running) {
+            return running;
+        }
+    }
+}
+
+/// Sets the prefix of the names of the threads spawned by the global executor (the default is `"fibers-global"`).
+///
+/// If the global executor already has started,
+/// the invocation of this function has no effect and `false` is returned.
+///
+/// This is equivalent to `GlobalExecutorBuilder::new().thread_name_prefix(prefix).try_init()`.
+/// The background thread is named `"{prefix}-run"`, and the scheduler threads are named `"{prefix}-{i}"` at the OS level
+/// (see `GlobalExecutorBuilder::thread_name_prefix` for the limitations).
+/// This is useful for processes running multiple runtimes, whose threads must be told apart.
+pub fn set_thread_name_prefix<T: Into<String>>(prefix: T) -> bool {
+    GlobalExecutorBuilder::new()
+        .thread_name_prefix(prefix)
+        .try_init()
+}
+
+/// Returns the number of scheduler threads used by the global executor.
+///
+/// If the global executor has not started yet, this function starts it
+/// so that the returned value always agrees with the running executor.
+pub fn thread_count() -> usize {
+    if cfg!(feature = "no-threads") {
+        return 1;
+    }
+    with_global_executor(|executor| executor.thread_count)
+}
+
+/// Returns `true` if the global executor has started, otherwise `false`.
+///
+/// Unlike the other functions of this crate, this never starts the executor
+/// (i.e., no threads are spawned as a side effect).
+/// After `shutdown` is called, this returns `false` until the executor is started again.
+///
+/// Note that the result may be outdated immediately if other threads use the global executor concurrently.
+pub fn is_started() -> bool {
+    global::is_started()
+}
+
+/// Builds a new `ThreadPoolExecutor` configured in the same way as the global executor.
+///
+/// The executor uses the thread count that the global executor uses
+/// (i.e., the one set by `set_thread_count` or the default one), but calling this does not fix it.
+///
+/// The returned executor is independent of the global one:
+/// it is neither run nor shared, so the caller is responsible for running it (e.g., on a thread of their choosing).
+///
+/// With the `no-threads` feature, this always returns an error since the executor would spawn threads.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers;
+/// # extern crate fibers_global;
+/// # extern crate futures;
+/// use fibers::{Executor, Spawn};
+///
+/// # fn main() {
+/// # if cfg!(feature = "no-threads") { return; }
+/// let mut executor = fibers_global::build_executor().unwrap();
+/// let monitor = executor.spawn_monitor(futures::finished::<_, ()>(1));
+/// assert_eq!(executor.run_future(monitor).unwrap().ok(), Some(1));
+/// # }
+/// ```
+pub fn build_executor() -> io::Result<ThreadPoolExecutor> {
+    if cfg!(feature = "no-threads") {
+        return Err(io::Error::other(
+            "`fibers_global::build_executor` is not available with the `no-threads` feature",
+        ));
+    }
+    global::new_executor(global::peek_thread_count())
+}
+
+/// Shuts down the global executor.
+///
+/// This stops the background thread of the executor and waits until it exits.
+/// The fibers remaining on the executor are dropped without being completed,
+/// so threads blocking in `execute` on them will panic as if the executor aborted.
+///
+/// The executor is not dead after this call:
+/// the next `spawn`, `execute` or `handle` invocation starts a fresh executor
+/// with the same number of threads as before.
+///
+/// If the global executor has not started, this function does nothing.
+///
+/// With the `no-threads` feature, this drops the thread-local executor of the current thread (unless called inside a fiber).
+pub fn shutdown() {
+    #[cfg(feature = "no-threads")]
+    no_threads::shutdown();
+    global::stop();
+}
+
+/// Waits for the in-flight fibers to complete at most `timeout`, and then shuts down the global executor.
+///
+/// This is an orderly variant of [`shutdown`] (e.g., for handling `SIGTERM`).
+/// Returns `true` if all the fibers completed (i.e., [`in_flight_fibers`] reached `0`) before the timeout.
+/// Otherwise the remaining fibers are dropped as with `shutdown`.
+///
+/// While draining, new fibers are not accepted: [`spawn`] (and the variants returning nothing, such as
+/// `spawn_after`) drops the given future without running it, and [`try_spawn`] returns `Err(NotStartedError)`.
+/// Note that this also applies to the fibers spawned by the draining fibers themselves.
+/// The functions returning a monitor or blocking the caller (e.g., `spawn_monitor`, `execute` and `scope`)
+/// are not affected, since their callers wait for the results.
+///
+/// [`shutdown`]: ./fn.shutdown.html
+/// [`in_flight_fibers`]: ./fn.in_flight_fibers.html
+/// [`spawn`]: ./fn.spawn.html
+/// [`try_spawn`]: ./fn.try_spawn.html
+pub fn drain_and_shutdown(timeout: Duration) -> bool {
+    global::set_draining(true);
+    let drained = wait_for_idle(timeout);
+    #[cfg(feature = "no-threads")]
+    no_threads::shutdown();
+    global::stop();
+    global::set_draining(false);
+    drained
+}
+
+/// Shuts down the global executor and forgets its configuration (e.g., the thread count).
+///
+/// After this call, the next `spawn`, `execute` or `handle` invocation starts a fresh executor
+/// with the default configuration, and `set_thread_count` (or `GlobalExecutorBuilder`) can be used again.
+/// This is intended to isolate tests that configure the global executor.
+///
+/// # Hazards
+///
+/// This function is only available with the `test-reset` feature and should not be used in production code.
+/// As with `shutdown`, the fibers remaining on the executor are dropped and threads blocking in `execute`
+/// on them will panic. Besides, code relying on the invariant that the configuration never changes once
+/// the executor has started (e.g., a cached `thread_count()`) may observe inconsistent values.
+/// Handles obtained before the reset keep referring to the stopped executor.
+#[cfg(feature = "test-reset")]
+pub fn reset_for_tests() {
+    global::reset();
+}
+
+/// Makes the next start of the global executor fail to spawn its background thread.
+///
+/// This is only available with the `test-fault-injection` feature to test the failure path.
+#[cfg(feature = "test-fault-injection")]
+#[doc(hidden)]
+pub fn inject_thread_spawn_failure(fail: bool) {
+    global::inject_thread_spawn_failure(fail);
+}
+
+/// Spawns a fiber to execute the given future by using the global `ThreadPoolExecutor`.
+///
+/// If the `tracing` feature is enabled, a `fiber` span is created for the fiber and entered on each poll.
+/// Its `id` field is a new [`FiberId`] (also returned by [`current_fiber_id`] during the poll,
+/// and by [`spawn_monitor_with_id`] to the caller).
+/// The same applies to all the other functions spawning fibers (e.g., [`spawn_monitor`], [`spawn_on`] and [`into_global`]),
+/// but not to the fibers of the functions executing futures (e.g., [`execute`]).
+///
+/// [`FiberId`]: ./struct.FiberId.html
+/// [`current_fiber_id`]: ./fn.current_fiber_id.html
+/// [`spawn_monitor`]: ./fn.spawn_monitor.html
+/// [`spawn_on`]: ./fn.spawn_on.html
+/// [`spawn_monitor_with_id`]: ./fn.spawn_monitor_with_id.html
+/// [`into_global`]: ./fn.into_global.html
+/// [`execute`]: ./fn.execute.html
+pub fn spawn<F>(future: F)
+where
+    F: Future<Item = (), Error = ()> + Send + 'static,
+{
+    let _ = checked_spawn(future);
+}
+
+/// Spawns a fiber to execute the given future by using the global `ThreadPoolExecutor`,
+/// or returns an error if the fiber would not be run.
+///
+/// This is the same as [`spawn`] except that callers can know that `future` has been dropped without being run:
+///
+/// - `Err(SpawnError::ExecutorDead)` is returned if the executor has aborted and will not be restarted
+///   (i.e., [`executor_health`] is `Dead`).
+/// - `Err(SpawnError::Draining)` is returned while [`drain_and_shutdown`] is in progress.
+///
+/// [`spawn`]: ./fn.spawn.html
+/// [`executor_health`]: ./fn.executor_health.html
+/// [`drain_and_shutdown`]: ./fn.drain_and_shutdown.html
+///
+/// # Examples
+///
+/// ```
+/// assert!(fibers_global::checked_spawn(futures::lazy(|| Ok(()))).is_ok());
+/// ```
+pub fn checked_spawn<F>(future: F) -> Result<(), SpawnError>
+where
+    F: Future<Item = (), Error = ()> + Send + 'static,
+{
+    if global::is_draining() {
+        return Err(SpawnError::Draining);
+    }
+    let future = panic_handler::wrap(future);
+    #[cfg(feature = "tracing")]
+    let future = trace::instrument(FiberId::next(), future);
+    let future = metrics::track(future);
+    #[cfg(feature = "no-threads")]
+    {
+        no_threads::with_handle(|h| h.spawn(future));
+        Ok(())
+    }
+    #[cfg(not(feature = "no-threads"))]
+    {
+        let cached = global::cached_handle();
+        if cached.dead {
+            return Err(SpawnError::ExecutorDead);
+        }
+        cached.handle.spawn(future);
+        Ok(())
+    }
+}
+
+/// Spawns a fiber to execute the given future if the global `ThreadPoolExecutor` has already started.
+///
+/// Unlike [`spawn`], this never starts the executor: if it has not started (or has been shut down),
+/// `Err(NotStartedError)` is returned and `future` is dropped.
+/// This is useful for libraries that use the global executor only if the application does.
+///
+/// Note that the executor may be shut down by another thread right after this function succeeds,
+/// in which case the spawned fiber is dropped without being completed.
+///
+/// With the `no-threads` feature, the thread-local executor is always available, so this is the same as `spawn`
+/// except for the return value.
+///
+/// [`spawn`]: ./fn.spawn.html
+pub fn try_spawn<F>(future: F) -> Result<(), NotStartedError>
+where
+    F: Future<Item = (), Error = ()> + Send + 'static,
+{
+    if global::is_draining() {
+        return Err(NotStartedError);
+    }
+    let future = panic_handler::wrap(future);
+    #[cfg(feature = "tracing")]
+    let future = trace::instrument(FiberId::next(), future);
+    let future = metrics::track(future);
+    #[cfg(feature = "no-threads")]
+    {
+        no_threads::with_handle(|h| h.spawn(future));
+        Ok(())
+    }
+    #[cfg(not(feature = "no-threads"))]
+    global::with_running_executor(|executor| executor.handle.spawn(future)).ok_or(NotStartedError)
+}
+
+/// Spawns a fiber to execute the given future by using the global `ThreadPoolExecutor`
+/// after the number of in-flight fibers falls below `max_in_flight`.
+///
+/// The calling thread is blocked until then, which gives backpressure to producers spawning fibers
+/// faster than the executor can complete them. The number is the same as [`in_flight_fibers`],
+/// so it includes the fibers spawned by the other functions of this crate.
+/// The limit applies only to this invocation: if different limits are used at the same time,
+/// each call waits for its own limit (i.e., the resulting behavior is up to the callers).
+///
+/// Since this blocks the current thread, it should not be called inside a fiber.
+///
+/// # Panics
+///
+/// If `max_in_flight` is `0`, the current thread will panic.
+///
+/// [`in_flight_fibers`]: ./fn.in_flight_fibers.html
+pub fn spawn_bounded<F>(future: F, max_in_flight: usize)
+where
+    F: Future<Item = (), Error = ()> + Send + 'static,
+{
+    assert_ne!(max_in_flight, 0);
+    if global::is_draining() {
+        return;
+    }
+    let future = panic_handler::wrap(future);
+    #[cfg(feature = "tracing")]
+    let future = trace::instrument(FiberId::next(), future);
+    let future = metrics::track_bounded(future, max_in_flight);
+    with_spawner(|h| h.spawn(future));
+}
+
+/// Spawns a fiber that executes the given future after `delay` by using the global `ThreadPoolExecutor`.
+///
+/// The delay is measured from the invocation of this function by a timer on the global executor,
+/// so the calling thread is not blocked.
+pub fn spawn_after<F>(delay: Duration, future: F)
+where
+    F: Future<Item = (), Error = ()> + Send + 'static,
+{
+    spawn(timer::timeout(delay).then(move |_| future));
+}
+
+/// Spawns a fiber that executes the given future after `delay` by using the global `ThreadPoolExecutor`
+/// and returns a future to monitor it's execution result.
+///
+/// See [`spawn_after`] for how the delay is measured.
+///
+/// [`spawn_after`]: ./fn.spawn_after.html
+pub fn spawn_monitor_after<F>(delay: Duration, future: F) -> Monitor<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    spawn_monitor(timer::timeout(delay).then(move |_| future))
+}
+
+/// Spawns a fiber to execute the future built by the given closure by using the global `ThreadPoolExecutor`.
+///
+/// This is equivalent to `spawn(futures::lazy(f))`,
+/// so the future is constructed on a worker thread of the executor.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers_global;
+/// use std::sync::mpsc;
+///
+/// # fn main() {
+/// # if cfg!(feature = "no-threads") { return; }
+/// let (tx, rx) = mpsc::channel();
+/// fibers_global::spawn_fn(move || tx.send(1).map_err(|_| ()));
+/// assert_eq!(rx.recv().ok(), Some(1));
+/// # }
+/// ```
+pub fn spawn_fn<F, T>(f: F)
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: IntoFuture<Item = (), Error = ()> + 'static,
+    T::Future: Send + 'static,
+{
+    spawn(futures::lazy(f));
+}
+
+/// Spawns a fiber to execute the given fallible future by using the global `ThreadPoolExecutor`,
+/// reporting its error if it fails.
+///
+/// This removes the boilerplate of `spawn(future.map(|_| ()).map_err(|e| eprintln!(..)))`.
+/// The error is logged at the error level if the `log` feature is enabled, otherwise printed to the standard error.
+/// The item is discarded. Otherwise, this is the same as [`spawn`].
+///
+/// [`spawn`]: ./fn.spawn.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::err;
+///
+/// fibers_global::spawn_logged(err::<(), _>("something went wrong"));
+/// ```
+pub fn spawn_logged<F>(future: F)
+where
+    F: Future + Send + 'static,
+    F::Error: fmt::Display,
+{
+    spawn(future.then(|result| {
+        if let Err(e) = result {
+            global::report_error(format_args!("A fiber failed: {}", e));
+        }
+        Ok(())
+    }));
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns a future to monitor it's execution result.
+pub fn spawn_monitor<F>(future: F) -> Monitor<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let future = panic_handler::wrap(future);
+    #[cfg(feature = "tracing")]
+    let future = trace::instrument(FiberId::next(), future);
+    with_spawn_handle(future, |h, f| h.spawn_monitor(f))
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns a future to monitor
+/// the execution result transformed by `map`.
+///
+/// `map` is applied inside the spawned fiber, so heavy post-processing does not run on the calling thread.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers_global;
+/// # extern crate futures;
+/// use futures::Future;
+///
+/// # fn main() {
+/// let monitor = fibers_global::spawn_monitor_map(futures::finished::<_, ()>(2), |v| v * 10);
+/// assert_eq!(fibers_global::execute(monitor.map_err(|_| ())), Ok(20));
+/// # }
+/// ```
+pub fn spawn_monitor_map<F, T, M>(future: F, map: M) -> Monitor<T, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Error: Send + 'static,
+    T: Send + 'static,
+    M: FnOnce(F::Item) -> T + Send + 'static,
+{
+    spawn_monitor(future.map(map))
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns a future to monitor
+/// the execution result of the continuation built by `next`.
+///
+/// This is a shorthand of `spawn_monitor(future.and_then(next))`: `next` is called inside the spawned fiber
+/// with the item of `future`, so the continuation also runs on the executor rather than on the calling thread.
+/// If `future` fails, `next` is not called and the error is monitored as is.
+///
+/// # Examples
+///
+/// ```
+/// use futures::Future;
+///
+/// let monitor = fibers_global::spawn_monitor_then(futures::finished::<_, ()>(2), |v| Ok(v * 10));
+/// assert_eq!(fibers_global::execute(monitor.map_err(|_| ())), Ok(20));
+/// ```
+pub fn spawn_monitor_then<F, N, B>(future: F, next: N) -> Monitor<B::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Error: Send + 'static,
+    N: FnOnce(F::Item) -> B + Send + 'static,
+    B: IntoFuture<Error = F::Error> + 'static,
+    B::Future: Send + 'static,
+    B::Item: Send + 'static,
+{
+    spawn_monitor(future.and_then(next))
+}
+
+/// Spawns a fiber to execute the future built by the given closure by using the global `ThreadPoolExecutor`
+/// and returns a future to monitor it's execution result.
+///
+/// This is equivalent to `spawn_monitor(futures::lazy(f))`,
+/// so the future is constructed on a worker thread of the executor.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers_global;
+/// # extern crate futures;
+/// use futures::Future;
+///
+/// # fn main() {
+/// let monitor = fibers_global::spawn_monitor_fn(|| Ok::<_, ()>((0..10).sum::<usize>()));
+/// assert_eq!(fibers_global::execute(monitor.map_err(|_| ())), Ok(45));
+/// # }
+/// ```
+pub fn spawn_monitor_fn<F, T>(f: F) -> Monitor<T::Item, T::Error>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: IntoFuture + 'static,
+    T::Future: Send + 'static,
+    T::Item: Send + 'static,
+    T::Error: Send + 'static,
+{
+    spawn_monitor(futures::lazy(f))
+}
+
+/// Spawns a fiber to execute the given boxed future by using the global `ThreadPoolExecutor`
+/// and returns a future to monitor it's execution result.
+///
+/// This is useful when futures are built dynamically and stored as trait objects.
+pub fn spawn_monitor_boxed<T, E>(
+    future: Box<dyn Future<Item = T, Error = E> + Send>,
+) -> Monitor<T, E>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    spawn_monitor(future)
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns a future to monitor it's execution result
+/// whose error is boxed as a trait object.
+///
+/// The error is converted inside the spawned fiber, so monitors of futures with different error types
+/// can be handled uniformly (e.g., stored in a single `Vec`).
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::{err, ok};
+/// use std::fmt;
+///
+/// let monitors = vec![
+///     fibers_global::spawn_monitor_erased(ok::<_, std::io::Error>(1)),
+///     fibers_global::spawn_monitor_erased(err::<_, fmt::Error>(fmt::Error)),
+/// ];
+/// let results = monitors
+///     .into_iter()
+///     .map(|m| fibers_global::execute(m).is_ok())
+///     .collect::<Vec<_>>();
+/// assert_eq!(results, vec![true, false]);
+/// ```
+pub fn spawn_monitor_erased<F>(future: F) -> Monitor<F::Item, Box<dyn Error + Send>>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Error + Send + 'static,
+{
+    spawn_monitor(future.map_err(|e| Box::new(e) as Box<dyn Error + Send>))
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns a future to monitor it's execution result
+/// within the given timeout.
+///
+/// The timer is also scheduled on the global executor.
+/// If the fiber does not complete before the timer expires,
+/// it is dropped and the returned future fails with `MonitorTimeoutError::Timeout`.
+/// If both become ready at the same time, the result of the fiber wins.
+///
+/// Unlike `Monitor`, the returned future can be polled by any executor.
+pub fn spawn_monitor_timeout<F>(
+    future: F,
+    timeout: Duration,
+) -> impl Future<Item = F::Item, Error = MonitorTimeoutError<F::Error>>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let future = future
+        .select2(timer::timeout(timeout))
+        .then(|result| match result {
+            Ok(FutureEither::A((v, _))) => Ok(v),
+            Err(FutureEither::A((e, _))) => Err(MonitorTimeoutError::Failed(e)),
+            Ok(FutureEither::B(_)) => Err(MonitorTimeoutError::Timeout),
+            Err(FutureEither::B(_)) => Err(MonitorTimeoutError::Aborted),
+        });
+    #[cfg(feature = "tracing")]
+    let future = trace::instrument(FiberId::next(), future);
+    with_spawn_handle(future, TaskMonitor::spawn).map_err(|e| match e {
+        MonitorError::Aborted => MonitorTimeoutError::Aborted,
+        MonitorError::Failed(e) => e,
+    })
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns a future to monitor it's execution result.
+///
+/// Unlike [`spawn_monitor`], a panic of the fiber is caught and reported as `CaughtError::Panicked`
+/// instead of aborting the global executor. Note that the fiber panic handler is not called for caught panics.
+///
+/// The future is wrapped in `AssertUnwindSafe`, so `F` (and its captured state) is not required to be `UnwindSafe`.
+/// This is sound as long as the state shared with other fibers (e.g., via `Arc<Mutex<_>>`) can tolerate
+/// being observed after a panic; the future itself is dropped right after it panicked.
+///
+/// [`spawn_monitor`]: ./fn.spawn_monitor.html
+///
+/// # Examples
+///
+/// ```
+/// use fibers_global::CaughtError;
+/// use futures::future::lazy;
+///
+/// let monitor = fibers_global::spawn_monitor_catch_unwind(lazy(|| -> Result<(), ()> { panic!("oops") }));
+/// match fibers_global::execute(monitor) {
+///     Err(fibers::sync::oneshot::MonitorError::Failed(CaughtError::Panicked(payload))) => {
+///         assert_eq!(payload.downcast_ref::<&str>(), Some(&"oops"));
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn spawn_monitor_catch_unwind<F>(future: F) -> Monitor<F::Item, CaughtError<F::Error>>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let future = AssertUnwindSafe(future)
+        .catch_unwind()
+        .then(|result| match result {
+            Ok(Ok(v)) => Ok(v),
+            Ok(Err(e)) => Err(CaughtError::Failed(e)),
+            Err(payload) => Err(CaughtError::Panicked(payload)),
+        });
+    #[cfg(feature = "tracing")]
+    let future = trace::instrument(FiberId::next(), future);
+    with_spawn_handle(future, |h, future| h.spawn_monitor(future))
+}
+
+/// Spawns a fiber to execute the given future by using the global `ThreadPoolExecutor`
+/// and returns a future to wait for the result without blocking.
+///
+/// Unlike [`execute`], this is intended for composing the future into a larger one,
+/// which may be run by any executor (the returned future notifies non-fiber tasks too).
+/// Unlike `spawn_monitor`, the error type is that of `future`.
+///
+/// # Panics
+///
+/// If the global executor aborts, polling the returned future will panic like [`execute`].
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::Future;
+///
+/// let future = fibers_global::into_global(futures::lazy(|| Ok::<_, ()>(1)));
+/// assert_eq!(future.map(|v| v * 2).wait(), Ok(2));
+/// ```
+pub fn into_global<F>(future: F) -> impl Future<Item = F::Item, Error = F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    spawn_task_monitor(future).map_err(|e| match e {
+        MonitorError::Aborted => panic!("{}", ExecutorAborted),
+        MonitorError::Failed(e) => e,
+    })
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns a future to monitor it's execution result,
+/// which can be cloned to share the result among multiple consumers.
+///
+/// The returned future is a `futures::future::Shared`, so:
+///
+/// - Its item is `SharedItem<F::Item>` and its error is `SharedError<MonitorError<F::Error>>`,
+///   which dereference to the original values. Getting an owned value requires cloning it, so
+///   `F::Item: Clone` (or `F::Error: Clone`) is needed only for that (e.g., `(*item).clone()`).
+/// - It must be polled in a `futures` task (e.g., by `Future::wait` or another executor).
+///   Since `fibers` does not provide a task context, polling it inside a fiber (e.g., via [`execute`]) panics.
+///
+/// The inner monitor notifies non-fiber tasks (like [`into_global`]), so each consumer is woken up when the fiber exits.
+///
+/// [`execute`]: ./fn.execute.html
+/// [`into_global`]: ./fn.into_global.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::Future;
+///
+/// let shared = fibers_global::spawn_shared(futures::lazy(|| Ok::<_, ()>(1)));
+/// let other = shared.clone();
+/// assert_eq!(*shared.wait().unwrap(), 1);
+/// assert_eq!(*other.wait().unwrap(), 1);
+/// ```
+pub fn spawn_shared<F>(
+    future: F,
+) -> Shared<impl Future<Item = F::Item, Error = MonitorError<F::Error>>>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    spawn_task_monitor(future).shared()
+}
+
+/// Runs the given blocking function on a dedicated OS thread and returns a future to monitor the result.
+///
+/// Since fibers are scheduled cooperatively, calling blocking functions (e.g., filesystem I/O) inside a fiber
+/// stalls its scheduler thread. This function runs `f` outside of the global executor instead.
+///
+/// A new thread is spawned for each invocation (i.e., the number of the threads is unbounded).
+/// If `f` panics, the returned monitor fails with `MonitorError::Aborted`.
+///
+/// With the `no-threads` feature, `f` is called inside a fiber of the thread-local executor instead.
+pub fn spawn_blocking<F, T>(f: F) -> Monitor<T, ()>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (monitored, monitor) = oneshot::monitor();
+    if cfg!(feature = "no-threads") {
+        let future = futures::lazy(move || {
+            if let Ok(v) = std::panic::catch_unwind(AssertUnwindSafe(f)) {
+                monitored.exit(Ok(v));
+            }
+            Ok(())
+        });
+        with_spawn_handle(future, |h, f| h.spawn(f));
+    } else {
+        thread::spawn(move || monitored.exit(Ok(f())));
+    }
+    monitor
+}
+
+/// Returns the handle of the global `ThreadPoolExecutor`.
+///
+/// # Panics
+///
+/// If the global executor has not started and its threads cannot be spawned (e.g., due to `ulimit`),
+/// the current thread will panic with a message including the thread count and the OS error.
+/// The same applies to all the functions that start the executor.
+///
+/// With the `no-threads` feature, there is no `ThreadPoolExecutor` to return, so this always panics.
+/// The same applies to [`with_handle`].
+///
+/// Each thread caches a clone of the handle until the executor is restarted or shut down,
+/// so repeated calls on the same thread do not lock the global executor.
+/// The same applies to [`spawn`] and the other functions spawning fibers on the global executor.
+///
+/// [`with_handle`]: ./fn.with_handle.html
+/// [`spawn`]: ./fn.spawn.html
+pub fn handle() -> ThreadPoolExecutorHandle {
+    #[cfg(feature = "no-threads")]
+    no_threads::unsupported("handle");
+    #[cfg(not(feature = "no-threads"))]
+    global::cached_handle().handle
+}
+
+/// Returns the handle of the global `ThreadPoolExecutor` if it has already started.
+///
+/// Unlike [`handle`], this never starts the executor: `None` is returned without side effects
+/// if it has not started (or has been shut down). This is useful for libraries that reuse the global
+/// executor only if the application has started it (see also [`is_started`] and [`try_spawn`]).
+///
+/// Note that the executor may be shut down by another thread right after this function returns `Some`,
+/// in which case the fibers spawned via the handle are dropped without being completed.
+///
+/// [`handle`]: ./fn.handle.html
+/// [`is_started`]: ./fn.is_started.html
+/// [`try_spawn`]: ./fn.try_spawn.html
+pub fn handle_if_started() -> Option<ThreadPoolExecutorHandle> {
+    global::with_running_executor(|executor| executor.handle.clone())
+}
+
+/// Calls `f` with a reference to the handle of the global `ThreadPoolExecutor`.
+///
+/// Unlike [`handle`], this does not clone the handle, but the global executor is locked on each call.
+///
+/// `f` is called while holding the lock of the global executor,
+/// so it must not call the other functions of this crate (it will deadlock).
+///
+/// [`handle`]: ./fn.handle.html
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers;
+/// # extern crate fibers_global;
+/// # extern crate futures;
+/// use fibers::Spawn;
+/// use futures::Future;
+///
+/// # fn main() {
+/// # if cfg!(feature = "no-threads") { return; }
+/// let monitor = fibers_global::with_handle(|h| h.spawn_monitor(futures::finished::<_, ()>(1)));
+/// assert_eq!(fibers_global::execute(monitor.map_err(|_| ())), Ok(1));
+/// # }
+/// ```
+pub fn with_handle<F, R>(f: F) -> R
+where
+    F: FnOnce(&ThreadPoolExecutorHandle) -> R,
+{
+    #[cfg(feature = "no-threads")]
+    {
+        let _ = f;
+        no_threads::unsupported("with_handle");
+    }
+    #[cfg(not(feature = "no-threads"))]
+    with_global_executor(|executor| f(&executor.handle))
+}
+
+/// Calls `f` with the handle of the global executor and `future` wrapped to be counted as a spawned fiber.
+fn with_spawn_handle<F, G, R>(future: F, f: G) -> R
+where
+    F: Future,
+    G: FnOnce(&SpawnHandle, metrics::Tracked<F>) -> R,
+{
+    let future = metrics::track(future);
+    with_spawner(|h| f(h, future))
+}
+
+/// The handle used by this crate to spawn fibers.
+///
+/// This is the handle of the thread-local executor if the `no-threads` feature is enabled.
+#[cfg(not(feature = "no-threads"))]
+type SpawnHandle = ThreadPoolExecutorHandle;
+#[cfg(feature = "no-threads")]
+type SpawnHandle = no_threads::Handle;
+
+/// Calls `f` with a reference to the `SpawnHandle`.
+fn with_spawner<F, R>(f: F) -> R
+where
+    F: FnOnce(&SpawnHandle) -> R,
+{
+    #[cfg(feature = "no-threads")]
+    {
+        no_threads::with_handle(f)
+    }
+    #[cfg(not(feature = "no-threads"))]
+    f(&global::cached_handle().handle)
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result.
+///
+/// # Panics
+///
+/// If the global executor aborted, the current thread will panic.
+///
+/// In debug builds, this function also panics if it is called on a scheduler thread of the global executor
+/// (see [`on_pool_thread`]). Blocking a worker on fibers which need the same pool to make progress
+/// may deadlock (always if the pool has only one thread). In release builds, just a warning is printed instead.
+/// The same applies to all the variants of this function that block the calling thread.
+///
+/// With the `no-threads` feature, waiting inside a fiber is impossible (the fibers of the current thread
+/// cannot make progress), so it is handled as if the executor aborted: this function panics,
+/// and the fallible variants (e.g., [`try_execute_result`]) return an error. See [`execute_or_spawn`] for code
+/// that may be called inside a fiber.
+///
+/// [`on_pool_thread`]: ./fn.on_pool_thread.html
+/// [`try_execute_result`]: ./fn.try_execute_result.html
+/// [`execute_or_spawn`]: ./fn.execute_or_spawn.html
+pub fn execute<F>(future: F) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    execute_with(&with_spawner(SpawnHandle::clone), metrics::track(future))
+}
+
+/// Executes the given CPU-heavy future by using the global `ThreadPoolExecutor` and waits the result.
+///
+/// The behavior is the same as [`execute`]. In debug builds, this function additionally warns if
+/// the pool seems to have no idle workers (i.e., [`queue_depth`] is not `0`), since a long-running
+/// computation on a saturated pool delays the other fibers. Such work may be better suited to [`spawn_blocking`].
+/// The check is a single atomic read and is skipped in release builds.
+///
+/// [`execute`]: ./fn.execute.html
+/// [`queue_depth`]: ./fn.queue_depth.html
+/// [`spawn_blocking`]: ./fn.spawn_blocking.html
+pub fn execute_cpu_bound<F>(future: F) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    #[cfg(debug_assertions)]
+    {
+        let queue_depth = queue_depth();
+        if queue_depth != 0 {
+            global::report_warning(format_args!(
+                "`fibers_global::execute_cpu_bound` is called while {} fibers are waiting to be run; \
+                 consider `fibers_global::spawn_blocking` for CPU-heavy work",
+                queue_depth
+            ));
+        }
+    }
+    execute(future)
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result,
+/// resuming a panic of the fiber on the calling thread.
+///
+/// Unlike [`execute`], a panic of the fiber is caught (as with [`spawn_monitor_catch_unwind`]) instead of aborting
+/// the global executor, and then resumed by `std::panic::resume_unwind` with the original payload.
+/// So a test calling this fails at the call site with the original message.
+/// Note that the backtrace (if printed by the panic hook) is that of the scheduler thread,
+/// and the resumed panic does not call the panic hook again.
+///
+/// [`execute`]: ./fn.execute.html
+/// [`spawn_monitor_catch_unwind`]: ./fn.spawn_monitor_catch_unwind.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::lazy;
+///
+/// let result = std::panic::catch_unwind(|| {
+///     fibers_global::execute_propagating_panic(lazy(|| -> Result<(), ()> { panic!("oops") }))
+/// });
+/// assert!(result.is_err());
+/// ```
+pub fn execute_propagating_panic<F>(future: F) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    match execute(AssertUnwindSafe(future).catch_unwind()) {
+        Ok(result) => result,
+        Err(payload) => std::panic::resume_unwind(payload),
+    }
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result,
+/// returning it with the elapsed wall-clock time.
+///
+/// The time covers from the submission (including the spawn of the fiber) to the wake-up of the calling thread.
+/// Otherwise, this is the same as [`execute`].
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// let (result, elapsed) = fibers_global::execute_timed(futures::lazy(|| Ok::<_, ()>(1)));
+/// assert_eq!(result, Ok(1));
+/// println!("{:?}", elapsed);
+/// ```
+pub fn execute_timed<F>(future: F) -> (Result<F::Item, F::Error>, Duration)
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let start = Instant::now();
+    let result = execute(future);
+    (result, start.elapsed())
+}
+
+/// Executes the given future and waits the result if the current thread is not a scheduler thread,
+/// otherwise spawns it as a fiber.
+///
+/// Blocking on a scheduler thread of the global executor may deadlock (see [`on_pool_thread`]).
+/// This function is useful for library code that may be called in both contexts:
+///
+/// - Off the pool, this behaves like [`execute`] and returns `Some(result)`.
+/// - On the pool, the future is [`spawn`]ed without waiting (its result is discarded), and `None` is returned.
+///
+/// [`on_pool_thread`]: ./fn.on_pool_thread.html
+/// [`execute`]: ./fn.execute.html
+/// [`spawn`]: ./fn.spawn.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::lazy;
+///
+/// let result = fibers_global::execute_or_spawn(lazy(|| Ok::<_, ()>(1)));
+/// assert_eq!(result, Some(Ok(1)));
+/// ```
+pub fn execute_or_spawn<F>(future: F) -> Option<Result<F::Item, F::Error>>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    if on_pool_thread() {
+        spawn(future.then(|_| Ok(())));
+        None
+    } else {
+        Some(execute(future))
+    }
+}
+
+/// Executes the given future by using the given executor and waits the result.
+///
+/// This is the same as [`execute`] except that the fiber is spawned by `handle`
+/// instead of the global executor, so it can be used with any `fibers` executor.
+/// Note that the executor must be run by another thread, otherwise this function blocks forever.
+///
+/// # Panics
+///
+/// If the executor aborted, the current thread will panic.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers;
+/// # extern crate fibers_global;
+/// # extern crate futures;
+/// use fibers::{Executor, ThreadPoolExecutor};
+///
+/// # fn main() {
+/// let mut executor = ThreadPoolExecutor::new().unwrap();
+/// let handle = executor.handle();
+/// std::thread::spawn(move || executor.run());
+///
+/// let result = fibers_global::execute_with(&handle, futures::finished::<_, ()>(1));
+/// assert_eq!(result, Ok(1));
+/// # }
+/// ```
+pub fn execute_with<H, F>(handle: &H, future: F) -> Result<F::Item, F::Error>
+where
+    H: Spawn,
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    try_execute_result_with(handle, future).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits until it completes.
+///
+/// This is a shorthand of `let _ = execute(future);` for futures executed only for their side effects.
+/// Like [`execute`], this function panics if the global executor aborted.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers_global;
+/// # extern crate futures;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// # fn main() {
+/// let counter = Arc::new(AtomicUsize::new(0));
+/// let c = Arc::clone(&counter);
+/// fibers_global::execute_unit(futures::lazy(move || {
+///     c.fetch_add(1, Ordering::SeqCst);
+///     Ok(())
+/// }));
+/// assert_eq!(counter.load(Ordering::SeqCst), 1);
+/// # }
+/// ```
+pub fn execute_unit<F>(future: F)
+where
+    F: Future<Item = (), Error = ()> + Send + 'static,
+{
+    let _ = execute(future);
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result.
+///
+/// Unlike [`execute`], this function returns `Err(ExecutorAborted)` instead of panicking
+/// if the global executor aborted.
+///
+/// [`execute`]: ./fn.execute.html
+pub fn try_execute_result<F>(future: F) -> Result<Result<F::Item, F::Error>, ExecutorAborted>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    try_execute_result_with(&with_spawner(SpawnHandle::clone), metrics::track(future))
+}
+
+fn try_execute_result_with<H, F>(
+    handle: &H,
+    future: F,
+) -> Result<Result<F::Item, F::Error>, ExecutorAborted>
+where
+    H: Spawn,
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let _guard = BlockingGuard::new();
+    let result = Waiter::spawn(handle, future).wait();
+    check_aborted(result)
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result.
+///
+/// Unlike [`execute`], this function returns `Err(ExecuteError::Aborted)` instead of panicking
+/// if the global executor aborted.
+///
+/// [`execute`]: ./fn.execute.html
+pub fn execute_checked<F>(future: F) -> Result<F::Item, ExecuteError<F::Error>>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    match try_execute_result(future) {
+        Err(ExecutorAborted) => Err(ExecuteError::Aborted),
+        Ok(result) => result.map_err(ExecuteError::Failed),
+    }
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result
+/// whose error is converted by `map`.
+///
+/// This is a shorthand of `execute(future).map_err(map)`,
+/// so `map` is called on the calling thread after the wait.
+/// Like [`execute`], this function panics if the global executor aborted (`map` is not called in that case).
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers_global;
+/// # extern crate futures;
+/// # fn main() {
+/// let result = fibers_global::execute_map_err(futures::failed::<(), _>(1), |e| format!("error: {}", e));
+/// assert_eq!(result, Err("error: 1".to_owned()));
+/// # }
+/// ```
+pub fn execute_map_err<F, G, E2>(future: F, map: G) -> Result<F::Item, E2>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+    G: FnOnce(F::Error) -> E2,
+{
+    execute(future).map_err(map)
+}
+
+/// Executes the given future whose item is a `Result` by using the global `ThreadPoolExecutor`
+/// and waits the flattened result.
+///
+/// This is a shorthand of `execute(future).and_then(|r| r)`.
+/// Like [`execute`], this function panics if the global executor aborted.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers_global;
+/// # extern crate futures;
+/// use futures::future::{err, ok};
+///
+/// # fn main() {
+/// assert_eq!(fibers_global::execute_ok(ok::<Result<_, ()>, _>(Ok(1))), Ok(1));
+/// assert_eq!(fibers_global::execute_ok(ok::<Result<(), _>, _>(Err("inner"))), Err("inner"));
+/// assert_eq!(fibers_global::execute_ok(err::<Result<(), _>, _>("outer")), Err("outer"));
+/// # }
+/// ```
+pub fn execute_ok<F, T, E>(future: F) -> Result<T, E>
+where
+    F: Future<Item = Result<T, E>, Error = E> + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    execute(future).and_then(|result| result)
+}
+
+/// Converts the given value into a future, executes it by using the global `ThreadPoolExecutor` and waits the result.
+///
+/// This is the same as [`execute`] except that anything implementing `IntoFuture` (e.g., `Result`) is accepted.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::ok;
+///
+/// assert_eq!(fibers_global::execute_into(ok::<_, ()>(1)), Ok(1));
+/// assert_eq!(fibers_global::execute_into(Err::<(), _>("failed")), Err("failed"));
+/// ```
+pub fn execute_into<T>(value: T) -> Result<T::Item, T::Error>
+where
+    T: IntoFuture,
+    T::Future: Send + 'static,
+    T::Item: Send + 'static,
+    T::Error: Send + 'static,
+{
+    execute(value.into_future())
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result
+/// while polling it at the given interval.
+///
+/// The calling thread is woken up as soon as the fiber exits (same as [`execute`]),
+/// but it also re-polls the result every `interval` even if no notification has arrived.
+/// A zero `interval` makes the calling thread spin instead of sleeping.
+///
+/// [`execute`]: ./fn.execute.html
+pub fn execute_with_poll_interval<F>(future: F, interval: Duration) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let _guard = BlockingGuard::new();
+    let result = with_spawn_handle(future, Waiter::spawn).wait_with_interval(interval);
+    into_execute_result(result)
+}
 
-static THREAD_COUNT: AtomicUsize = AtomicUsize::new(0);
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result,
+/// calling `progress` while waiting.
+///
+/// `progress` is called on the calling thread each time it wakes up before the future completes,
+/// which happens at least every 100 milliseconds. This is useful for printing a spinner or a heartbeat
+/// during long operations. Since the result is not observed while `progress` is running, it should return quickly.
+///
+/// Like [`execute`], this function panics if the global executor aborted.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// use fibers::time::timer;
+/// use futures::Future;
+/// use std::time::Duration;
+///
+/// let future = timer::timeout(Duration::from_millis(10)).map_err(|_| ());
+/// let mut ticks = 0;
+/// assert_eq!(fibers_global::execute_with_progress(future, || ticks += 1), Ok(()));
+/// ```
+pub fn execute_with_progress<F, P>(future: F, progress: P) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+    P: FnMut(),
+{
+    const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
 
-fn get_thread_count() -> usize {
-    match THREAD_COUNT.swap(std::usize::MAX, Ordering::SeqCst) {
-        0 => num_cpus::get(),
-        n => n,
-    }
+    let _guard = BlockingGuard::new();
+    let result =
+        with_spawn_handle(future, Waiter::spawn).wait_with_progress(PROGRESS_INTERVAL, progress);
+    into_execute_result(result)
 }
 
-/// Sets the number of scheduler threads used by the global executor.
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result.
 ///
-/// If the global executor already has started,
-/// the invocation of this function has no effect and `false` is returned.
+/// This is an alias of [`execute`] following the naming of other async runtimes.
 ///
-/// # Panics
+/// Note that the future is executed on the worker threads of the global executor,
+/// so only the calling thread is blocked until it completes.
 ///
-/// If the specified count is `0` or `usize::MAX`, the current thread will panic.
-pub fn set_thread_count(n: usize) -> bool {
-    assert_ne!(n, 0);
-    assert_ne!(n, std::usize::MAX);
+/// [`execute`]: ./fn.execute.html
+pub fn block_on<F>(future: F) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    execute(future)
+}
 
-    loop {
-        let current = THREAD_COUNT.load(Ordering::SeqCst);
-        if current == std::usize::MAX {
-            return false;
-        }
-        if THREAD_COUNT.compare_and_swap(current, n, Ordering::SeqCst) == current {
-            return true;
-        }
-    }
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result at most `timeout`.
+///
+/// If the future does not complete within the timeout, `Err(TimeoutError)` is returned and
+/// the fiber executing it is abandoned (i.e., it will be dropped without being completed).
+pub fn execute_timeout<F>(
+    future: F,
+    timeout: Duration,
+) -> Result<Result<F::Item, F::Error>, TimeoutError>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    execute_deadline(future, Instant::now() + timeout)
 }
 
-lazy_static! {
-    static ref GLOBAL_EXECUTOR: ThreadPoolExecutorHandle = {
-        use fibers::Executor;
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result at most `timeout`,
+/// falling back to `default` on timeout.
+///
+/// The three outcomes are handled as follows:
+///
+/// - If the future succeeds within the timeout, `Ok(item)` is returned.
+/// - If the future fails within the timeout, its error is returned as `Err(e)`.
+/// - If the future does not complete within the timeout, `Ok(default)` is returned and
+///   the fiber is abandoned like [`execute_timeout`].
+///
+/// Like [`execute`], this function panics if the global executor aborted.
+///
+/// [`execute_timeout`]: ./fn.execute_timeout.html
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::{empty, ok};
+/// use std::time::Duration;
+///
+/// let timeout = Duration::from_millis(10);
+/// assert_eq!(fibers_global::execute_timeout_or(ok::<_, ()>(1), timeout, 0), Ok(1));
+/// assert_eq!(fibers_global::execute_timeout_or(empty::<_, ()>(), timeout, 0), Ok(0));
+/// ```
+pub fn execute_timeout_or<F>(
+    future: F,
+    timeout: Duration,
+    default: F::Item,
+) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    execute_timeout_or_else(future, timeout, || default)
+}
 
-        let executor = fibers::ThreadPoolExecutor::with_thread_count(get_thread_count())
-            .expect("Cannot create the global `ThreadPoolExecutor`");
-        let handle = executor.handle();
-        std::thread::spawn(move || {
-            executor
-                .run()
-                .expect("The global `ThreadPoolExecutor` aborted")
-        });
-        handle
-    };
+/// Same as [`execute_timeout_or`] except that the default value is computed by `default` only on timeout.
+///
+/// [`execute_timeout_or`]: ./fn.execute_timeout_or.html
+pub fn execute_timeout_or_else<F, G>(
+    future: F,
+    timeout: Duration,
+    default: G,
+) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+    G: FnOnce() -> F::Item,
+{
+    execute_timeout(future, timeout).unwrap_or_else(|TimeoutError| Ok(default()))
 }
 
-/// Spawns a fiber to execute the given future by using the global `ThreadPoolExecutor`.
-pub fn spawn<F>(future: F)
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result until `deadline`.
+///
+/// This is the same as [`execute_timeout`] except that the limit is given as an absolute time,
+/// which is convenient when several futures must complete before a shared deadline.
+/// The result is polled at least once, so an already resolved future does not time out
+/// even if `deadline` has passed.
+///
+/// [`execute_timeout`]: ./fn.execute_timeout.html
+pub fn execute_deadline<F>(
+    future: F,
+    deadline: Instant,
+) -> Result<Result<F::Item, F::Error>, TimeoutError>
 where
-    F: Future<Item = (), Error = ()> + Send + 'static,
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
 {
-    handle().spawn(future);
+    let _guard = BlockingGuard::new();
+    let result = with_spawn_handle(future, Waiter::spawn)
+        .wait_until(deadline)
+        .ok_or(TimeoutError)?;
+    Ok(into_execute_result(result))
 }
 
-/// Spawns a fiber by using the global `ThreadPoolExecutor` and returns a future to monitor it's execution result.
-pub fn spawn_monitor<F>(future: F) -> Monitor<F::Item, F::Error>
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result unless `cancel` resolves first.
+///
+/// This is useful when the cancellation signal comes from elsewhere (e.g., a `ctrl_c` future):
+///
+/// - If the future completes first, its result is returned as `Ok(Some(item))` or `Err(e)`.
+/// - If `cancel` completes (or fails) first, `Ok(None)` is returned and the future is dropped without being completed.
+///
+/// Both futures are polled by the same fiber, and `future` is polled before `cancel`.
+/// So if both are ready at the same time, the future wins.
+///
+/// Like [`execute`], this function panics if the global executor aborted.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::{empty, ok};
+///
+/// assert_eq!(fibers_global::execute_with_cancel(ok::<_, ()>(1), empty()), Ok(Some(1)));
+/// assert_eq!(fibers_global::execute_with_cancel(empty::<(), ()>(), ok(())), Ok(None));
+/// ```
+pub fn execute_with_cancel<F, C>(future: F, cancel: C) -> Result<Option<F::Item>, F::Error>
 where
     F: Future + Send + 'static,
     F::Item: Send + 'static,
     F::Error: Send + 'static,
+    C: Future<Item = (), Error = ()> + Send + 'static,
 {
-    handle().spawn_monitor(future)
+    execute(future.select2(cancel).then(|result| match result {
+        Ok(FutureEither::A((v, _))) => Ok(Some(v)),
+        Err(FutureEither::A((e, _))) => Err(e),
+        Ok(FutureEither::B(_)) | Err(FutureEither::B(_)) => Ok(None),
+    }))
 }
 
-/// Returns the handle of the global `ThreadPoolExecutor`.
-pub fn handle() -> ThreadPoolExecutorHandle {
-    GLOBAL_EXECUTOR.clone()
+/// Executes the given future by using a temporary `ThreadPoolExecutor` with `thread_count` threads and waits the result.
+///
+/// This is useful for running a heavy computation on a private pool sized for it,
+/// without contending with the fibers on the global executor.
+/// The temporary executor is driven by the calling thread and is dropped when the future completes.
+/// Its scheduler threads (named `fibers-isolated-{i}` at the OS level on Linux) exit shortly after this returns.
+///
+/// Note that the global functions (e.g., `spawn`) called inside the future still use the global executor.
+///
+/// With the `no-threads` feature, this is the same as [`execute`] (`thread_count` is only checked).
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Panics
+///
+/// If `thread_count` is `0`, or the temporary executor cannot be created or aborts (e.g., the future panicked),
+/// the current thread will panic.
+///
+/// # Examples
+///
+/// ```
+/// let result = fibers_global::execute_isolated(futures::lazy(|| Ok::<_, ()>(1 + 2)), 2);
+/// assert_eq!(result, Ok(3));
+/// ```
+pub fn execute_isolated<F>(future: F, thread_count: usize) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    assert_ne!(thread_count, 0);
+    if cfg!(feature = "no-threads") {
+        return execute(future);
+    }
+    let _guard = BlockingGuard::new();
+    let mut executor = global::new_executor(thread_count).unwrap_or_else(|e| {
+        panic!(
+            "Cannot create a temporary `ThreadPoolExecutor` (thread_count={}): {}",
+            thread_count, e
+        )
+    });
+    let (sentinels, _workers) = global::spawn_sentinels(
+        &executor.handle(),
+        thread_count,
+        "fibers-isolated",
+        &Default::default(),
+        false,
+    );
+    let monitor = executor.spawn_monitor(future);
+    let result = executor
+        .run_fiber(monitor)
+        .unwrap_or_else(|e| panic!("The temporary `ThreadPoolExecutor` aborted: {}", e));
+
+    // The executor is dropped before its sentinels (see `spawn_sentinels`).
+    drop(executor);
+    drop(sentinels);
+    into_execute_result(result)
 }
 
-/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result.
-pub fn execute<F>(future: F) -> Result<F::Item, F::Error>
+/// Executes the given future on the calling thread and waits the result.
+///
+/// Unlike [`execute`], this function does not use the global executor at all:
+/// the future is polled on the calling thread by a temporary `fibers::InPlaceExecutor`.
+/// Thus the future does not need to be `Send` or `'static` (e.g., it can capture `Rc`),
+/// at the cost of not running in parallel with it.
+///
+/// The future is polled (at least) every millisecond until it completes.
+///
+/// # Panics
+///
+/// If the `InPlaceExecutor` cannot be created or it fails, the current thread will panic.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// # extern crate fibers_global;
+/// # extern crate futures;
+/// use std::rc::Rc;
+///
+/// # fn main() {
+/// let value = Rc::new(1);
+/// let result = fibers_global::execute_local(futures::lazy(|| Ok::<_, ()>(*value + 1)));
+/// assert_eq!(result, Ok(2));
+/// # }
+/// ```
+pub fn execute_local<F: Future>(future: F) -> Result<F::Item, F::Error> {
+    InPlaceExecutor::new()
+        .and_then(|mut executor| executor.run_future(future))
+        .unwrap_or_else(|e| panic!("The `InPlaceExecutor` failed: {}", e))
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and polls the result only once.
+///
+/// If the future has not completed yet, `Ok(None)` is returned.
+/// Even in that case, the spawned fiber remains scheduled and will run to completion
+/// (its result is discarded).
+pub fn try_execute<F>(future: F) -> Result<Option<F::Item>, F::Error>
 where
     F: Future + Send + 'static,
     F::Item: Send + 'static,
     F::Error: Send + 'static,
 {
-    let mut monitor = handle().spawn_monitor(future);
-    loop {
-        match monitor.poll() {
-            Err(MonitorError::Aborted) => panic!("The global `ThreadPoolExecutor` aborted"),
-            Err(MonitorError::Failed(e)) => return Err(e),
-            Ok(Async::Ready(v)) => return Ok(v),
-            Ok(Async::NotReady) => {
-                std::thread::sleep(Duration::from_millis(1));
-            }
-        }
+    let mut monitor = spawn_monitor(future);
+    into_execute_result(monitor.poll()).map(|v| match v {
+        Async::Ready(v) => Some(v),
+        Async::NotReady => None,
+    })
+}
+
+/// Spawns a fiber and returns a `TaskMonitor` to monitor it from outside of fibers.
+///
+/// As with `spawn_monitor`, a panic of the fiber is reported to the fiber panic handler.
+fn spawn_task_monitor<F>(future: F) -> TaskMonitor<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let future = panic_handler::wrap(future);
+    #[cfg(feature = "tracing")]
+    let future = trace::instrument(FiberId::next(), future);
+    with_spawn_handle(future, TaskMonitor::spawn)
+}
+
+fn into_execute_result<T, E>(result: Result<T, MonitorError<E>>) -> Result<T, E> {
+    check_aborted(result).unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn check_aborted<T, E>(
+    result: Result<T, MonitorError<E>>,
+) -> Result<Result<T, E>, ExecutorAborted> {
+    match result {
+        Err(MonitorError::Aborted) => Err(ExecutorAborted),
+        Err(MonitorError::Failed(e)) => Ok(Err(e)),
+        Ok(v) => Ok(Ok(v)),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use fibers::sync::oneshot;
-    use futures::{lazy, Future};
+    use futures::{empty, finished, lazy};
+    use std::time::{Duration, Instant};
 
     use super::*;
 
@@ -158,4 +1699,442 @@ mod tests {
         assert_eq!(result.ok(), Some(3));
     }
 
+    #[cfg(not(feature = "no-threads"))]
+    #[test]
+    fn execute_wakes_up_immediately() {
+        // The waiting thread is woken up by the fiber instead of polling the monitor periodically
+        // (which would wake it up about 50 times here), so this does not depend on the load of the host.
+        let parks = || waiter::PARKS.with(|parks| parks.get());
+        let before = parks();
+        let future = timer::timeout(Duration::from_millis(50));
+        assert_eq!(execute(future), Ok(()));
+        let wakeups = parks() - before;
+        assert!(wakeups <= 5, "{}", wakeups);
+    }
+
+    #[test]
+    fn execute_timeout_works() {
+        let result = execute_timeout(lazy(|| Ok::<_, ()>(1)), Duration::from_secs(10));
+        assert_eq!(result, Ok(Ok(1)));
+
+        let (tx, rx) = oneshot::channel::<()>();
+        let future = empty::<(), ()>().then(move |r| {
+            let _ = tx;
+            r
+        });
+        let result = execute_timeout(future, Duration::from_millis(10));
+        assert_eq!(result, Err(TimeoutError));
+
+        // The abandoned fiber has been dropped.
+        assert!(execute(rx).is_err());
+    }
+
+    #[test]
+    fn spawn_monitor_boxed_works() {
+        let futures: Vec<Box<dyn Future<Item = usize, Error = ()> + Send>> = vec![
+            Box::new(finished(1)),
+            Box::new(lazy(|| Ok(2))),
+            Box::new(timer::timeout(Duration::from_millis(1)).then(|_| Ok(3))),
+        ];
+        let monitors = futures
+            .into_iter()
+            .map(spawn_monitor_boxed)
+            .collect::<Vec<_>>();
+        let result = execute(futures::future::join_all(monitors));
+        assert_eq!(result.ok(), Some(vec![1, 2, 3]));
+    }
+
+    #[cfg(not(feature = "no-threads"))]
+    #[test]
+    fn spawn_after_works() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let start = Instant::now();
+        spawn_after(
+            Duration::from_millis(20),
+            lazy(move || {
+                let _ = tx.send(Instant::now());
+                Ok(())
+            }),
+        );
+        let executed_at = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(executed_at - start >= Duration::from_millis(20));
+
+        let start = Instant::now();
+        let monitor = spawn_monitor_after(
+            Duration::from_millis(20),
+            lazy(|| Ok::<_, ()>(Instant::now())),
+        );
+        let executed_at = execute(monitor).unwrap();
+        assert!(executed_at - start >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn execute_local_works() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let future = {
+            let log = Rc::clone(&log);
+            timer::timeout(Duration::from_millis(5)).then(move |_| {
+                log.borrow_mut().push("done");
+                let len = log.borrow().len();
+                Ok::<_, ()>(len)
+            })
+        };
+        assert_eq!(execute_local(future), Ok(1));
+        assert_eq!(*log.borrow(), vec!["done"]);
+    }
+
+    #[test]
+    fn execute_deadline_works() {
+        // An already resolved future does not time out even if the deadline has passed.
+        let deadline = Instant::now();
+        std::thread::sleep(Duration::from_millis(1));
+        let result = execute_deadline(lazy(|| Ok::<_, ()>(1)), deadline);
+        assert_eq!(result, Ok(Ok(1)));
+
+        let deadline = Instant::now() + Duration::from_millis(10);
+        let result = execute_deadline(empty::<(), ()>(), deadline);
+        assert_eq!(result, Err(TimeoutError));
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[cfg(not(feature = "no-threads"))]
+    #[test]
+    fn try_execute_works() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let future = timer::timeout(Duration::from_millis(10)).then(move |_| {
+            let _ = tx.send(());
+            Ok::<_, ()>(())
+        });
+        assert_eq!(try_execute(future), Ok(None));
+
+        // The fiber keeps running after `try_execute` returned.
+        assert!(rx.recv_timeout(Duration::from_secs(10)).is_ok());
+    }
+
+    #[cfg(not(feature = "no-threads"))]
+    #[test]
+    fn thread_count_works() {
+        assert_eq!(thread_count(), global::default_thread_count());
+    }
+
+    #[test]
+    fn spawn_monitor_timeout_works() {
+        let monitor = spawn_monitor_timeout(lazy(|| Ok::<_, ()>(1)), Duration::from_secs(10));
+        assert_eq!(execute(monitor), Ok(1));
+
+        let monitor = spawn_monitor_timeout(empty::<(), ()>(), Duration::from_millis(10));
+        assert_eq!(execute(monitor), Err(MonitorTimeoutError::Timeout));
+
+        // The fiber wins if both are ready.
+        let monitor = spawn_monitor_timeout(finished::<_, ()>(2), Duration::from_secs(0));
+        assert_eq!(execute(monitor), Ok(2));
+
+        // Can be polled outside of fibers.
+        let monitor = spawn_monitor_timeout(lazy(|| Ok::<_, ()>(3)), Duration::from_secs(10));
+        assert_eq!(futures::executor::spawn(monitor).wait_future(), Ok(3));
+    }
+
+    #[test]
+    fn execute_with_poll_interval_works() {
+        let future = timer::timeout(Duration::from_millis(5)).map(|()| 1);
+        assert_eq!(
+            execute_with_poll_interval(future, Duration::from_millis(1)),
+            Ok(1)
+        );
+
+        let future = timer::timeout(Duration::from_millis(5)).map(|()| 2);
+        assert_eq!(
+            execute_with_poll_interval(future, Duration::from_secs(0)),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn spawn_monitor_catch_unwind_works() {
+        let monitor = spawn_monitor_catch_unwind(lazy(|| -> Result<(), ()> { panic!("caught") }));
+        match execute(monitor) {
+            Err(MonitorError::Failed(CaughtError::Panicked(payload))) => {
+                assert_eq!(payload.downcast_ref::<&str>(), Some(&"caught"));
+            }
+            _ => panic!(),
+        }
+
+        // The executor is still alive.
+        let monitor = spawn_monitor_catch_unwind(lazy(|| Err::<(), _>("failed")));
+        match execute(monitor) {
+            Err(MonitorError::Failed(CaughtError::Failed(e))) => assert_eq!(e, "failed"),
+            _ => panic!(),
+        }
+        let monitor = spawn_monitor_catch_unwind(lazy(|| Ok::<_, ()>(1)));
+        assert_eq!(execute(monitor).ok(), Some(1));
+    }
+
+    #[test]
+    fn execute_timeout_or_works() {
+        let timeout = Duration::from_millis(20);
+
+        let future = timer::timeout(Duration::from_millis(1)).then(|_| Ok::<_, ()>(1));
+        assert_eq!(execute_timeout_or(future, timeout, 0), Ok(1));
+
+        let future = timer::timeout(Duration::from_millis(1)).then(|_| Err::<usize, _>("failed"));
+        assert_eq!(execute_timeout_or(future, timeout, 0), Err("failed"));
+
+        assert_eq!(execute_timeout_or(empty::<usize, ()>(), timeout, 0), Ok(0));
+    }
+
+    #[test]
+    fn execute_with_cancel_works() {
+        let (tx, rx) = oneshot::channel::<()>();
+        let future = timer::timeout(Duration::from_millis(1)).then(|_| Ok::<_, ()>(1));
+        assert_eq!(execute_with_cancel(future, rx.map_err(|_| ())), Ok(Some(1)));
+        drop(tx);
+
+        let future = lazy(|| Err::<usize, _>("failed"));
+        assert_eq!(execute_with_cancel(future, empty()), Err("failed"));
+
+        // Cancelled.
+        let (tx, rx) = oneshot::channel();
+        let cancel = rx.map_err(|_| ());
+        let _ = tx.send(());
+        assert_eq!(execute_with_cancel(empty::<usize, ()>(), cancel), Ok(None));
+
+        let cancel = timer::timeout(Duration::from_millis(5)).map_err(|_| ());
+        assert_eq!(execute_with_cancel(empty::<usize, ()>(), cancel), Ok(None));
+
+        // The future wins if both are ready.
+        assert_eq!(
+            execute_with_cancel(finished::<_, ()>(1), finished(())),
+            Ok(Some(1))
+        );
+        assert_eq!(
+            execute_with_cancel(lazy(|| Err::<usize, _>("failed")), finished(())),
+            Err("failed")
+        );
+    }
+
+    #[test]
+    fn execute_timeout_or_else_works() {
+        let timeout = Duration::from_millis(20);
+        let mut called = false;
+        assert_eq!(
+            execute_timeout_or_else(finished::<_, ()>(1), timeout, || {
+                called = true;
+                0
+            }),
+            Ok(1)
+        );
+        assert!(!called);
+
+        assert_eq!(
+            execute_timeout_or_else(empty::<usize, ()>(), timeout, || 2),
+            Ok(2)
+        );
+        assert_eq!(
+            execute_timeout_or_else(lazy(|| Err::<usize, _>("failed")), timeout, || 2),
+            Err("failed")
+        );
+    }
+
+    #[test]
+    fn execute_cpu_bound_works() {
+        let future = lazy(|| Ok::<_, ()>((0..1000u64).sum::<u64>()));
+        assert_eq!(execute_cpu_bound(future), Ok(499500));
+        assert_eq!(
+            execute_cpu_bound(lazy(|| Err::<(), _>("failed"))),
+            Err("failed")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "propagated panic")]
+    fn execute_propagating_panic_works() {
+        assert_eq!(execute_propagating_panic(lazy(|| Ok::<_, ()>(1))), Ok(1));
+        assert_eq!(
+            execute_propagating_panic(lazy(|| Err::<(), _>("failed"))),
+            Err("failed")
+        );
+
+        let _ =
+            execute_propagating_panic(lazy(|| -> Result<(), ()> { panic!("propagated panic") }));
+    }
+
+    #[test]
+    fn execute_timed_works() {
+        let future = timer::timeout(Duration::from_millis(10)).then(|_| Ok::<_, ()>(1));
+        let (result, elapsed) = execute_timed(future);
+        assert_eq!(result, Ok(1));
+        assert!(elapsed >= Duration::from_millis(10), "{:?}", elapsed);
+
+        let (result, _) = execute_timed(lazy(|| Err::<(), _>("failed")));
+        assert_eq!(result, Err("failed"));
+    }
+
+    #[test]
+    fn execute_or_spawn_works() {
+        assert_eq!(execute_or_spawn(lazy(|| Ok::<_, ()>(1))), Some(Ok(1)));
+        assert_eq!(
+            execute_or_spawn(lazy(|| Err::<(), _>("failed"))),
+            Some(Err("failed"))
+        );
+
+        // On a scheduler thread, the future is spawned instead.
+        let (tx, rx) = oneshot::channel();
+        let future = lazy(move || {
+            let result = execute_or_spawn(lazy(move || {
+                let _ = tx.send(2);
+                Ok::<_, ()>(())
+            }));
+            Ok::<_, ()>(result.is_none())
+        });
+        assert_eq!(execute(future), Ok(true));
+        assert_eq!(execute(rx), Ok(2));
+    }
+
+    #[test]
+    fn spawn_monitor_then_works() {
+        let (tx0, rx0) = oneshot::channel();
+        let (tx1, rx1) = oneshot::channel();
+        let monitor = spawn_monitor_then(rx0, move |v: usize| rx1.map(move |w: usize| v + w));
+        let _ = tx0.send(1);
+        let _ = tx1.send(2);
+        assert_eq!(execute(monitor).ok(), Some(3));
+
+        // `next` is not called if the first future fails.
+        let (tx0, rx0) = oneshot::channel::<usize>();
+        let monitor = spawn_monitor_then(rx0, |_| -> Result<usize, _> { unreachable!() });
+        drop(tx0);
+        assert!(execute(monitor).is_err());
+    }
+
+    #[test]
+    fn execute_with_progress_works() {
+        let mut ticks = 0;
+        let future = timer::timeout(Duration::from_millis(250)).map(|()| 1);
+        assert_eq!(execute_with_progress(future, || ticks += 1), Ok(1));
+        assert!(ticks >= 1, "{}", ticks);
+
+        let result = execute_with_progress(lazy(|| Err::<(), _>("failed")), || {});
+        assert_eq!(result, Err("failed"));
+    }
+
+    #[test]
+    fn spawn_monitor_erased_works() {
+        let monitors = vec![
+            spawn_monitor_erased(lazy(|| Ok::<_, io::Error>(1))),
+            spawn_monitor_erased(lazy(|| Err::<usize, _>(TimeoutError))),
+            spawn_monitor_erased(lazy(|| Err::<usize, _>(io::Error::other("io")))),
+        ];
+        let results = monitors
+            .into_iter()
+            .map(|monitor| match execute(monitor) {
+                Ok(v) => Ok(v),
+                Err(MonitorError::Failed(e)) => Err(e.to_string()),
+                Err(MonitorError::Aborted) => panic!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![Ok(1), Err(TimeoutError.to_string()), Err("io".to_owned())]
+        );
+    }
+
+    #[test]
+    fn into_global_works() {
+        // Driven by the global executor itself.
+        let future = into_global(timer::timeout(Duration::from_millis(5)).map(|()| 1));
+        assert_eq!(execute(future).ok(), Some(1));
+
+        // Driven by a local executor (the thread-local executor cannot be run inside fibers of another one).
+        #[cfg(not(feature = "no-threads"))]
+        {
+            let future = into_global(timer::timeout(Duration::from_millis(5)).map(|()| 2));
+            assert_eq!(execute_local(future).ok(), Some(2));
+        }
+
+        // Driven by a non-fiber task.
+        let future = into_global(lazy(|| Err::<(), _>("failed")));
+        assert_eq!(
+            futures::executor::spawn(future).wait_future(),
+            Err("failed")
+        );
+    }
+
+    #[cfg(not(feature = "no-threads"))]
+    #[test]
+    fn spawn_shared_works() {
+        let shared = spawn_shared(timer::timeout(Duration::from_millis(10)).map(|()| 1));
+        let consumers = (0..2)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || *shared.wait().unwrap())
+            })
+            .collect::<Vec<_>>();
+        for consumer in consumers {
+            assert_eq!(consumer.join().unwrap(), 1);
+        }
+
+        // Errors are shared too.
+        let shared = spawn_shared(lazy(|| Err::<(), _>("failed")));
+        for _ in 0..2 {
+            let e = shared.clone().wait().err().unwrap();
+            assert_eq!(*e, MonitorError::Failed("failed"));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[cfg(not(feature = "no-threads"))]
+    #[test]
+    fn execute_isolated_works() {
+        fn isolated_threads() -> usize {
+            std::fs::read_dir("/proc/self/task")
+                .unwrap()
+                .filter_map(|task| std::fs::read_to_string(task.ok()?.path().join("comm")).ok())
+                .filter(|comm| comm.starts_with("fibers-isolated"))
+                .count()
+        }
+
+        let future = timer::timeout(Duration::from_millis(5)).map(|()| isolated_threads());
+        let threads = execute_isolated(future, 3).unwrap();
+        assert!(threads >= 3, "{}", threads);
+
+        let result = execute_isolated(lazy(|| Err::<(), _>("failed")), 1);
+        assert_eq!(result, Err("failed"));
+
+        // The temporary threads are not regarded as the ones of the global executor.
+        assert_eq!(
+            execute_isolated(lazy(|| Ok::<_, ()>(on_pool_thread())), 1),
+            Ok(false)
+        );
+
+        // The temporary threads exit shortly (other tests may run `execute_isolated` concurrently).
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while isolated_threads() != 0 {
+            assert!(Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn spawn_blocking_works() {
+        let monitor = spawn_blocking(|| {
+            std::thread::sleep(Duration::from_millis(5));
+            1
+        });
+        assert_eq!(execute(monitor).ok(), Some(1));
+
+        let monitor = spawn_blocking(|| -> usize { panic!() });
+        assert_eq!(execute(monitor).err(), Some(MonitorError::Aborted));
+    }
+
+    #[test]
+    fn execute_checked_works() {
+        assert_eq!(execute_checked(lazy(|| Ok::<_, ()>(1))), Ok(1));
+        assert_eq!(
+            execute_checked(lazy(|| Err::<(), _>("failed"))),
+            Err(ExecuteError::Failed("failed"))
+        );
+    }
 }
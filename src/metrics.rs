@@ -0,0 +1,233 @@
+use futures::{Future, Poll};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::waiter;
+
+static SPAWNED_COUNT: AtomicU64 = AtomicU64::new(0);
+static IN_FLIGHT_FIBERS: AtomicUsize = AtomicUsize::new(0);
+static BLOCKING_CALLERS: AtomicUsize = AtomicUsize::new(0);
+static QUEUED_FIBERS: AtomicUsize = AtomicUsize::new(0);
+
+/// A snapshot of the metrics of the global executor.
+///
+/// New fields may be added in the future.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GlobalMetrics {
+    /// The number of scheduler threads (see [`thread_count`](./fn.thread_count.html)).
+    pub thread_count: usize,
+
+    /// The cumulative number of spawned fibers (see [`spawned_count`](./fn.spawned_count.html)).
+    pub spawned_count: u64,
+
+    /// The number of spawned fibers that have not completed yet (see [`in_flight_fibers`](./fn.in_flight_fibers.html)).
+    pub in_flight_fibers: usize,
+
+    /// The number of threads blocking in `execute` (see [`blocking_callers`](./fn.blocking_callers.html)).
+    pub blocking_callers: usize,
+
+    /// The estimated number of fibers waiting to be run for the first time (see [`queue_depth`](./fn.queue_depth.html)).
+    pub queue_depth: usize,
+}
+
+/// Returns a snapshot of the metrics of the global executor.
+///
+/// Like [`thread_count`], this starts the global executor if it has not started yet.
+///
+/// [`thread_count`]: ./fn.thread_count.html
+pub fn metrics() -> GlobalMetrics {
+    let thread_count = crate::thread_count();
+    GlobalMetrics {
+        thread_count,
+        spawned_count: spawned_count(),
+        in_flight_fibers: in_flight_fibers(),
+        blocking_callers: blocking_callers(),
+        queue_depth: queue_depth(),
+    }
+}
+
+/// Wraps `future` to count it as a fiber spawned on the global executor.
+pub(crate) fn track<F: Future>(future: F) -> Tracked<F> {
+    SPAWNED_COUNT.fetch_add(1, Ordering::Relaxed);
+    IN_FLIGHT_FIBERS.fetch_add(1, Ordering::SeqCst);
+    Tracked::new(future)
+}
+
+/// Same as `track` but blocks the current thread until the number of in-flight fibers is less than `max_in_flight`.
+pub(crate) fn track_bounded<F: Future>(future: F, max_in_flight: usize) -> Tracked<F> {
+    let mut current = IN_FLIGHT_FIBERS.load(Ordering::SeqCst);
+    loop {
+        // With the `no-threads` feature, the limit is ignored if the fibers cannot make progress (see `waiter::sleep`).
+        if current >= max_in_flight && waiter::sleep(Duration::from_micros(100)) {
+            current = IN_FLIGHT_FIBERS.load(Ordering::SeqCst);
+            continue;
+        }
+        match IN_FLIGHT_FIBERS.compare_exchange_weak(
+            current,
+            current + 1,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            Ok(_) => break,
+            Err(actual) => current = actual,
+        }
+    }
+    SPAWNED_COUNT.fetch_add(1, Ordering::Relaxed);
+    Tracked::new(future)
+}
+
+/// A future counted as an in-flight fiber until it is dropped.
+///
+/// It is also counted as a queued fiber until it is polled for the first time.
+///
+/// Since it is decremented on drop, the count is kept correct even if the fiber panics or is abandoned.
+pub(crate) struct Tracked<F> {
+    future: F,
+    queued: bool,
+}
+impl<F> Tracked<F> {
+    fn new(future: F) -> Self {
+        QUEUED_FIBERS.fetch_add(1, Ordering::SeqCst);
+        Tracked {
+            future,
+            queued: true,
+        }
+    }
+
+    fn dequeue(&mut self) {
+        if self.queued {
+            self.queued = false;
+            QUEUED_FIBERS.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+impl<F: Future> Future for Tracked<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        self.dequeue();
+        self.future.poll()
+    }
+}
+impl<F> Drop for Tracked<F> {
+    fn drop(&mut self) {
+        self.dequeue();
+        IN_FLIGHT_FIBERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Returns the cumulative number of fibers spawned by the functions of this crate.
+///
+/// This includes the fibers spawned internally by `execute` and its variants,
+/// but not those spawned directly via [`handle`] or [`with_handle`].
+///
+/// [`handle`]: ./fn.handle.html
+/// [`with_handle`]: ./fn.with_handle.html
+pub fn spawned_count() -> u64 {
+    SPAWNED_COUNT.load(Ordering::Relaxed)
+}
+
+/// Returns the number of fibers spawned by the functions of this crate that have not completed yet.
+///
+/// Like [`spawned_count`], the fibers spawned directly via [`handle`] or [`with_handle`] are not included.
+///
+/// [`spawned_count`]: ./fn.spawned_count.html
+/// [`handle`]: ./fn.handle.html
+/// [`with_handle`]: ./fn.with_handle.html
+pub fn in_flight_fibers() -> usize {
+    IN_FLIGHT_FIBERS.load(Ordering::SeqCst)
+}
+
+/// Returns the estimated number of fibers spawned by the functions of this crate that are waiting to be run.
+///
+/// `fibers` does not expose the run queue of `ThreadPoolExecutor`, so this is a best-effort estimate
+/// that counts the in-flight fibers which have not been polled yet. Note that:
+///
+/// - Fibers that have been polled once and are waiting to be re-scheduled after a wakeup are not included.
+/// - Like [`in_flight_fibers`], the fibers spawned directly via [`handle`] or [`with_handle`] are not included.
+/// - The value may be momentarily stale since it is updated independently of the scheduler.
+///
+/// [`in_flight_fibers`]: ./fn.in_flight_fibers.html
+/// [`handle`]: ./fn.handle.html
+/// [`with_handle`]: ./fn.with_handle.html
+pub fn queue_depth() -> usize {
+    QUEUED_FIBERS.load(Ordering::SeqCst)
+}
+
+/// Blocks the current thread until all the fibers spawned by the functions of this crate complete.
+///
+/// If `timeout` expires before that, `false` is returned.
+///
+/// This is useful as a barrier after spawning fire-and-forget fibers (e.g., in tests).
+/// Note that calling this inside a fiber spawned by this crate never succeeds,
+/// since that fiber is also in-flight.
+pub fn wait_for_idle(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if in_flight_fibers() == 0 {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        if !waiter::sleep(std::cmp::min(deadline - now, Duration::from_millis(1))) {
+            return false;
+        }
+    }
+}
+
+/// Returns the number of threads currently blocking in `execute` or its variants.
+///
+/// This is useful for debugging deadlocks.
+pub fn blocking_callers() -> usize {
+    BLOCKING_CALLERS.load(Ordering::SeqCst)
+}
+
+/// A guard that counts the calling thread as a blocking caller until it is dropped.
+///
+/// Since it is decremented on drop, the count is kept correct even if the caller panics.
+///
+/// All the blocking waits of this crate create this guard, so it also checks that it is not created on a scheduler thread.
+pub(crate) struct BlockingGuard(());
+impl BlockingGuard {
+    pub fn new() -> Self {
+        crate::worker::check_not_on_pool_thread();
+        BLOCKING_CALLERS.fetch_add(1, Ordering::SeqCst);
+        BlockingGuard(())
+    }
+}
+impl Drop for BlockingGuard {
+    fn drop(&mut self) {
+        BLOCKING_CALLERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::finished;
+
+    use super::*;
+
+    #[test]
+    fn spawned_count_works() {
+        let before = spawned_count();
+        crate::spawn(finished(()));
+        let _ = crate::spawn_monitor(finished::<_, ()>(()));
+        let _ = crate::execute(finished::<_, ()>(()));
+
+        // Other tests may spawn fibers concurrently.
+        assert!(spawned_count() >= before + 3);
+    }
+
+    #[test]
+    fn metrics_works() {
+        let before = metrics();
+        let _ = crate::execute(finished::<_, ()>(()));
+        let after = metrics();
+        assert_eq!(after.thread_count, crate::thread_count());
+        assert!(after.spawned_count > before.spawned_count);
+    }
+}
@@ -0,0 +1,158 @@
+use fibers::sync::oneshot::{self, Monitor, MonitorError, Monitored};
+use fibers::Spawn;
+use futures::task::{self, AtomicTask};
+use futures::{Future, Poll};
+use std::sync::Arc;
+
+/// Polls the given monitor once.
+///
+/// This is just `monitor.poll()`, provided as a primitive for custom waiting strategies (e.g., bespoke event loops).
+/// Note that `Monitor` only notifies fibers: when this is called outside of a fiber,
+/// nothing is notified when the result becomes ready, so the caller must poll it again by itself.
+///
+/// # Examples
+///
+/// ```
+/// use futures::Async;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// # if cfg!(feature = "no-threads") { return; }
+/// let mut monitor = fibers_global::spawn_monitor(futures::lazy(|| Ok::<_, ()>(1)));
+/// loop {
+///     match fibers_global::poll_monitor_once(&mut monitor) {
+///         Ok(Async::NotReady) => thread::sleep(Duration::from_millis(1)),
+///         result => {
+///             assert_eq!(result.ok(), Some(Async::Ready(1)));
+///             break;
+///         }
+///     }
+/// }
+/// ```
+pub fn poll_monitor_once<T, E>(monitor: &mut Monitor<T, E>) -> Poll<T, MonitorError<E>> {
+    monitor.poll()
+}
+
+/// Spawns a fiber by using the global `ThreadPoolExecutor` and polls its monitor once.
+///
+/// The monitor is returned with the result of the first poll (see [`poll_monitor_once`]).
+/// The first poll is usually `NotReady`, since the fiber is run by another thread.
+///
+/// [`poll_monitor_once`]: ./fn.poll_monitor_once.html
+#[allow(clippy::type_complexity)]
+pub fn spawn_and_poll_once<F>(
+    future: F,
+) -> (
+    Monitor<F::Item, F::Error>,
+    Poll<F::Item, MonitorError<F::Error>>,
+)
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let mut monitor = crate::spawn_monitor(future);
+    let result = poll_monitor_once(&mut monitor);
+    (monitor, result)
+}
+
+/// A `Monitor` that can be polled by executors other than `fibers` too.
+///
+/// `Monitor` only notifies fibers, so tasks of other executors are
+/// notified by the monitored fiber itself when it exits.
+///
+/// With the `no-threads` feature, polling this in a task also runs the thread-local executor.
+pub(crate) struct TaskMonitor<T, E> {
+    monitor: Monitor<T, E>,
+    task: Arc<AtomicTask>,
+}
+impl<T, E> TaskMonitor<T, E>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    pub fn spawn<H, F>(handle: &H, future: F) -> Self
+    where
+        H: Spawn,
+        F: Future<Item = T, Error = E> + Send + 'static,
+    {
+        let (monitored, monitor) = oneshot::monitor();
+        let task = Arc::new(AtomicTask::new());
+        let mut notifier = TaskNotifier {
+            monitored: Some(monitored),
+            task: Arc::clone(&task),
+        };
+        handle.spawn(future.then(move |result| {
+            notifier.exit(result);
+            Ok(())
+        }));
+        TaskMonitor { monitor, task }
+    }
+}
+impl<T, E> Future for TaskMonitor<T, E> {
+    type Item = T;
+    type Error = MonitorError<E>;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if task::is_in_task() {
+            self.task.register();
+        }
+        let result = self.monitor.poll();
+        #[cfg(feature = "no-threads")]
+        {
+            // The thread-local executor makes progress only while the current thread drives it,
+            // so the polling task is re-notified after each run.
+            if let Ok(futures::Async::NotReady) = result {
+                if task::is_in_task() && crate::no_threads::run_once() {
+                    task::current().notify();
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Notifies the task polling `TaskMonitor` after the monitored result has been sent.
+///
+/// If the fiber is dropped before exiting, dropping this also notifies the task.
+struct TaskNotifier<T, E> {
+    monitored: Option<Monitored<T, E>>,
+    task: Arc<AtomicTask>,
+}
+impl<T, E> TaskNotifier<T, E> {
+    fn exit(&mut self, result: Result<T, E>) {
+        if let Some(monitored) = self.monitored.take() {
+            monitored.exit(result);
+        }
+        self.task.notify();
+    }
+}
+impl<T, E> Drop for TaskNotifier<T, E> {
+    fn drop(&mut self) {
+        let _ = self.monitored.take();
+        self.task.notify();
+    }
+}
+
+#[cfg(all(test, not(feature = "no-threads")))]
+mod tests {
+    use fibers::time::timer;
+    use futures::{Async, Future};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    #[test]
+    fn poll_monitor_once_works() {
+        let future = timer::timeout(Duration::from_millis(10)).map(|()| 1);
+        let (mut monitor, mut result) = spawn_and_poll_once(future);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while let Ok(Async::NotReady) = result {
+            assert!(Instant::now() < deadline);
+            thread::sleep(Duration::from_millis(1));
+            result = poll_monitor_once(&mut monitor);
+        }
+        assert_eq!(result.ok(), Some(Async::Ready(1)));
+    }
+}
@@ -0,0 +1,219 @@
+//! The thread-local executor used instead of the global one if the `no-threads` feature is enabled.
+use fibers::executor::InPlaceExecutorHandle;
+use fibers::{Executor, InPlaceExecutor, Spawn};
+use futures::Future;
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+thread_local! {
+    static EXECUTOR: RefCell<Option<InPlaceExecutor>> = const { RefCell::new(None) };
+    static HANDLE: RefCell<Option<Handle>> = const { RefCell::new(None) };
+    static PANIC: RefCell<Option<Box<dyn Any + Send>>> = const { RefCell::new(None) };
+}
+
+/// The handle of the thread-local executor.
+///
+/// The spawned fibers are wrapped to catch their panics, since a panic unwinding through the scheduler
+/// would leave the fiber context of `fibers` broken on the current thread (which outlives the executor here).
+#[derive(Debug, Clone)]
+pub(crate) struct Handle(InPlaceExecutorHandle);
+impl Spawn for Handle {
+    fn spawn_boxed(&self, fiber: Box<dyn Future<Item = (), Error = ()> + Send>) {
+        self.0
+            .spawn(AssertUnwindSafe(fiber).catch_unwind().then(|result| {
+                if let Err(payload) = result {
+                    PANIC.with(|p| {
+                        p.borrow_mut().get_or_insert(payload);
+                    });
+                }
+                Ok(())
+            }));
+    }
+}
+
+/// Calls `f` with the handle of the thread-local executor, creating the executor if needed.
+pub(crate) fn with_handle<F, R>(f: F) -> R
+where
+    F: FnOnce(&Handle) -> R,
+{
+    HANDLE.with(|handle| {
+        let mut handle = handle.borrow_mut();
+        if handle.is_none() {
+            let executor = InPlaceExecutor::new().unwrap_or_else(|e| {
+                panic!("Cannot create the thread-local `InPlaceExecutor`: {}", e)
+            });
+            *handle = Some(Handle(executor.handle()));
+            EXECUTOR.with(|e| *e.borrow_mut() = Some(executor));
+        }
+        f(handle.as_ref().expect("Never fails"))
+    })
+}
+
+/// Returns `true` if the current thread is running a fiber (of the thread-local executor or another one).
+pub(crate) fn is_running() -> bool {
+    fibers::fiber::with_current_context(|_| ()).is_some()
+}
+
+/// Runs one unit of the work of the thread-local executor (this waits for I/O events or timers at most a millisecond).
+///
+/// Returns `false` without doing anything if this is called inside a fiber,
+/// since `fibers` does not support nested schedulers.
+///
+/// If a fiber panicked, the executor is dropped (as the global one aborts) and the panic is resumed here.
+pub(crate) fn run_once() -> bool {
+    if is_running() {
+        return false;
+    }
+    with_handle(|_| ());
+    let mut executor = EXECUTOR
+        .with(|e| e.borrow_mut().take())
+        .expect("Never fails");
+    let result = executor.run_once();
+    if let Some(payload) = PANIC.with(|p| p.borrow_mut().take()) {
+        HANDLE.with(|h| h.borrow_mut().take());
+        drop(executor);
+        panic::resume_unwind(payload);
+    }
+    EXECUTOR.with(|e| *e.borrow_mut() = Some(executor));
+    result.unwrap_or_else(|e| panic!("The thread-local `InPlaceExecutor` failed: {}", e));
+    true
+}
+
+/// Drops the thread-local executor (and the fibers remaining on it) unless this is called inside a fiber.
+pub(crate) fn shutdown() {
+    if is_running() {
+        return;
+    }
+    HANDLE.with(|h| h.borrow_mut().take());
+    let executor = EXECUTOR.with(|e| e.borrow_mut().take());
+    drop(executor);
+}
+
+/// Panics because `name` needs the threads of the global executor.
+pub(crate) fn unsupported(name: &str) -> ! {
+    panic!(
+        "`fibers_global::{}` is not available with the `no-threads` feature",
+        name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use fibers::sync::oneshot;
+    use fibers::time::timer;
+    use futures::future::lazy;
+    use futures::Future;
+    use std::panic;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn spawned_fibers_run_on_the_calling_thread() {
+        let (tx, rx) = oneshot::channel();
+        crate::spawn(lazy(move || {
+            let _ = tx.send(std::thread::current().id());
+            Ok(())
+        }));
+        assert_eq!(crate::execute(rx), Ok(std::thread::current().id()));
+
+        let future = timer::timeout(Duration::from_millis(5)).then(|_| Ok::<_, ()>(1));
+        assert_eq!(crate::execute(future), Ok(1));
+        assert_eq!(
+            crate::execute(lazy(|| Err::<(), _>("failed"))),
+            Err("failed")
+        );
+    }
+
+    #[test]
+    fn fibers_can_spawn_fibers() {
+        let (tx, rx) = oneshot::channel();
+        crate::spawn(lazy(move || {
+            crate::spawn(lazy(move || {
+                let _ = tx.send(2);
+                Ok(())
+            }));
+            Ok(())
+        }));
+        assert_eq!(crate::execute(rx), Ok(2));
+    }
+
+    #[test]
+    fn monitors_are_driven_by_waits() {
+        let monitor = crate::spawn_monitor(lazy(|| Ok::<_, ()>(1)));
+        assert_eq!(crate::execute(monitor).ok(), Some(1));
+
+        let result = crate::execute_timeout(futures::empty::<(), ()>(), Duration::from_millis(5));
+        assert_eq!(result, Err(crate::TimeoutError));
+
+        let monitor =
+            crate::spawn_monitor_timeout(lazy(|| Ok::<_, ()>(2)), Duration::from_secs(10));
+        assert_eq!(monitor.wait().ok(), Some(2));
+    }
+
+    #[test]
+    fn nested_execute_fails() {
+        let future = lazy(|| Ok::<_, ()>(crate::try_execute_result(lazy(|| Ok::<_, ()>(1)))));
+        assert_eq!(crate::execute(future), Ok(Err(crate::ExecutorAborted)));
+
+        // `execute_or_spawn` spawns the future instead.
+        let (tx, rx) = oneshot::channel();
+        let future = lazy(move || {
+            let result = crate::execute_or_spawn(lazy(move || {
+                let _ = tx.send(2);
+                Ok::<_, ()>(())
+            }));
+            Ok::<_, ()>(result.is_none())
+        });
+        assert_eq!(crate::execute(future), Ok(true));
+        assert_eq!(crate::execute(rx), Ok(2));
+    }
+
+    #[test]
+    fn panics_are_resumed_on_the_calling_thread() {
+        let result =
+            panic::catch_unwind(|| crate::execute(lazy(|| -> Result<(), ()> { panic!("oops") })));
+        assert!(result.is_err());
+
+        // A new executor is created for the next fibers.
+        assert_eq!(crate::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    }
+
+    #[test]
+    fn scoped_and_batched_fibers_are_driven() {
+        let items = [1, 2, 3];
+        let sum = AtomicUsize::new(0);
+        crate::scope(|s| {
+            for item in &items {
+                let sum = &sum;
+                s.spawn(timer::timeout(Duration::from_millis(1)).then(move |_| {
+                    sum.fetch_add(*item, Ordering::SeqCst);
+                    Ok(())
+                }));
+            }
+        });
+        assert_eq!(sum.load(Ordering::SeqCst), 6);
+
+        let futures = (0..4).map(|i| lazy(move || Ok::<_, ()>(i)));
+        assert_eq!(crate::execute_all_limited(futures, 2), Ok(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn no_threads_are_started() {
+        assert_eq!(crate::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+        assert_eq!(crate::thread_count(), 1);
+        crate::warm_up();
+        assert!(!crate::is_started());
+        assert!(crate::handle_if_started().is_none());
+        assert!(crate::build_executor().is_err());
+
+        let (tx, rx) = oneshot::channel();
+        let future = lazy(move || {
+            let _ = tx.send(crate::global::native_thread_id());
+            Ok(())
+        });
+        assert!(crate::spawn_on(0, future).is_ok());
+        assert_eq!(crate::execute(rx).ok(), Some(crate::worker_thread_ids()[0]));
+        assert!(crate::spawn_on(1, lazy(|| Ok(()))).is_err());
+    }
+}
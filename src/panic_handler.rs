@@ -0,0 +1,97 @@
+use futures::future::{CatchUnwind, Either};
+use futures::{Async, Future, Poll};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, PoisonError, RwLock};
+
+type PanicHandler = Arc<dyn Fn(&Box<dyn Any + Send>) + Send + Sync>;
+
+lazy_static! {
+    static ref PANIC_HANDLER: RwLock<Option<PanicHandler>> = RwLock::new(None);
+}
+
+/// Sets the function called when a fiber spawned by [`spawn`] or [`spawn_monitor`] panics.
+///
+/// The handler is called with the panic payload on the worker thread where the panic occurred,
+/// and then the panic is propagated as usual (i.e., the global executor aborts).
+/// This is useful for logging panics of fibers or counting them.
+///
+/// The handler is only applied to the fibers spawned after it has been set.
+/// If no handler is set (the default), the futures are spawned as is.
+///
+/// [`spawn`]: ./fn.spawn.html
+/// [`spawn_monitor`]: ./fn.spawn_monitor.html
+pub fn set_fiber_panic_handler<F>(f: F)
+where
+    F: Fn(&Box<dyn Any + Send>) + Send + Sync + 'static,
+{
+    *PANIC_HANDLER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(f));
+}
+
+/// Wraps `future` to call the panic handler if it is set.
+pub(crate) fn wrap<F: Future>(future: F) -> Either<F, CatchPanic<F>> {
+    let handler = PANIC_HANDLER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    if handler.is_none() && !cfg!(feature = "log") {
+        return Either::A(future);
+    }
+    Either::B(CatchPanic {
+        future: AssertUnwindSafe(future).catch_unwind(),
+        handler,
+    })
+}
+
+/// Logs the panic of the fiber being polled on the current thread.
+#[cfg(feature = "log")]
+#[allow(clippy::borrowed_box)]
+fn log_panic(payload: &Box<dyn Any + Send>) {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()))
+        .unwrap_or("Box<dyn Any>");
+    let thread = std::thread::current();
+    let thread_name = thread
+        .name()
+        .map_or_else(|| format!("{:?}", thread.id()), str::to_owned);
+    match crate::current_fiber_id() {
+        Some(id) => log::error!(
+            "A fiber ({}) panicked on thread {}: {}",
+            id,
+            thread_name,
+            message
+        ),
+        None => log::error!("A fiber panicked on thread {}: {}", thread_name, message),
+    }
+}
+
+/// A future that calls the panic handler (and logs the panic if the `log` feature is enabled)
+/// when the inner future panics.
+pub(crate) struct CatchPanic<F: Future> {
+    future: CatchUnwind<AssertUnwindSafe<F>>,
+    handler: Option<PanicHandler>,
+}
+impl<F: Future> Future for CatchPanic<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match self.future.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(Ok(v))) => Ok(Async::Ready(v)),
+            Ok(Async::Ready(Err(e))) => Err(e),
+            Err(payload) => {
+                #[cfg(feature = "log")]
+                log_panic(&payload);
+                if let Some(ref handler) = self.handler {
+                    handler(&payload);
+                }
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use futures::Future;
+use std::thread;
+use std::time::Duration;
+
+use crate::execute;
+
+/// A policy describing how `execute_retry` retries failed attempts.
+///
+/// # Examples
+///
+/// ```
+/// use fibers_global::RetryPolicy;
+/// use std::time::Duration;
+///
+/// // At most 5 attempts with the delays of 10ms, 20ms, 40ms and 80ms in between.
+/// let policy = RetryPolicy::exponential(5, Duration::from_millis(10));
+/// assert_eq!(policy.delay(2), Duration::from_millis(40));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: usize,
+    delay: Duration,
+    exponential: bool,
+}
+impl RetryPolicy {
+    /// Makes a new `RetryPolicy` that waits `delay` before each retry.
+    ///
+    /// # Panics
+    ///
+    /// If `max_attempts` is `0`, the current thread will panic.
+    pub fn fixed(max_attempts: usize, delay: Duration) -> Self {
+        assert_ne!(max_attempts, 0);
+        RetryPolicy {
+            max_attempts,
+            delay,
+            exponential: false,
+        }
+    }
+
+    /// Makes a new `RetryPolicy` that waits `initial_delay` before the first retry
+    /// and doubles the delay after each retry.
+    ///
+    /// # Panics
+    ///
+    /// If `max_attempts` is `0`, the current thread will panic.
+    pub fn exponential(max_attempts: usize, initial_delay: Duration) -> Self {
+        RetryPolicy {
+            exponential: true,
+            ..Self::fixed(max_attempts, initial_delay)
+        }
+    }
+
+    /// Returns the maximum number of attempts (including the first one).
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Returns the delay before the `retry`-th retry (starting from `0`).
+    pub fn delay(&self, retry: usize) -> Duration {
+        if !self.exponential || self.delay == Duration::from_secs(0) {
+            return self.delay;
+        }
+        let mut delay = self.delay;
+        for _ in 0..retry {
+            match delay.checked_mul(2) {
+                Some(d) => delay = d,
+                None => return Duration::MAX,
+            }
+        }
+        delay
+    }
+}
+
+/// Executes the futures made by `make_future` by using the global `ThreadPoolExecutor` until one of them succeeds.
+///
+/// Each attempt is executed by [`execute`] with a fresh future (completed futures cannot be polled again).
+/// If an attempt fails, the calling thread sleeps as described by `policy` and then retries.
+/// When the attempts are exhausted, the error of the last attempt is returned.
+///
+/// Like [`execute`], this function panics if the global executor aborted.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// use fibers_global::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let mut attempts = 0;
+/// let result = fibers_global::execute_retry(
+///     || {
+///         attempts += 1;
+///         if attempts < 3 { Err(attempts) } else { Ok(attempts) }
+///     },
+///     RetryPolicy::fixed(5, Duration::from_millis(1)),
+/// );
+/// assert_eq!(result, Ok(3));
+/// ```
+pub fn execute_retry<F, B>(
+    mut make_future: F,
+    policy: RetryPolicy,
+) -> Result<<B::Future as Future>::Item, <B::Future as Future>::Error>
+where
+    F: FnMut() -> B,
+    B: futures::IntoFuture,
+    B::Future: Send + 'static,
+    B::Item: Send + 'static,
+    B::Error: Send + 'static,
+{
+    let mut retry = 0;
+    loop {
+        match execute(make_future().into_future()) {
+            Ok(v) => return Ok(v),
+            Err(e) if retry + 1 >= policy.max_attempts => return Err(e),
+            Err(_) => {}
+        }
+        thread::sleep(policy.delay(retry));
+        retry += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execute_retry_succeeds_on_second_attempt() {
+        let mut attempts = 0;
+        let result = execute_retry(
+            || {
+                attempts += 1;
+                futures::lazy(move || {
+                    if attempts == 1 {
+                        Err("first")
+                    } else {
+                        Ok(attempts)
+                    }
+                })
+            },
+            RetryPolicy::fixed(3, Duration::from_millis(1)),
+        );
+        assert_eq!(result, Ok(2));
+        assert_eq!(attempts, 2);
+    }
+
+    #[test]
+    fn execute_retry_gives_up_after_max_attempts() {
+        let mut attempts = 0;
+        let result = execute_retry(
+            || {
+                attempts += 1;
+                Err::<(), _>(attempts)
+            },
+            RetryPolicy::exponential(3, Duration::from_millis(1)),
+        );
+        assert_eq!(result, Err(3));
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn retry_policy_delay_works() {
+        let policy = RetryPolicy::fixed(3, Duration::from_millis(5));
+        assert_eq!(policy.delay(0), Duration::from_millis(5));
+        assert_eq!(policy.delay(10), Duration::from_millis(5));
+
+        let policy = RetryPolicy::exponential(3, Duration::from_millis(5));
+        assert_eq!(policy.delay(0), Duration::from_millis(5));
+        assert_eq!(policy.delay(3), Duration::from_millis(40));
+        assert_eq!(policy.delay(100), Duration::MAX);
+    }
+}
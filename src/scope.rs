@@ -0,0 +1,212 @@
+use fibers::Spawn;
+use futures::{Future, Poll};
+use std::marker::PhantomData;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+use crate::metrics::BlockingGuard;
+use crate::{panic_handler, with_spawn_handle};
+
+/// Creates a scope for spawning fibers that borrow data from the enclosing stack frame.
+///
+/// All the fibers spawned via [`Scope::spawn`] are waited before this function returns
+/// (even if `f` panics), so they can safely borrow anything that outlives the call.
+/// This is similar to `std::thread::scope` and `rayon::scope`.
+///
+/// A fiber is regarded as finished when it is dropped by the global executor, i.e.,
+/// when it completes or the executor stops or aborts (e.g., if one of the fibers panicked).
+///
+/// Unlike [`spawn`], the fibers are accepted even while [`drain_and_shutdown`] is in progress,
+/// since this function waits for them like `execute`.
+///
+/// With the `no-threads` feature, the fibers cannot be waited inside a fiber,
+/// so the process is aborted if this is called inside a fiber that spawns some via the scope.
+///
+/// [`Scope::spawn`]: ./struct.Scope.html#method.spawn
+/// [`spawn`]: ./fn.spawn.html
+/// [`drain_and_shutdown`]: ./fn.drain_and_shutdown.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::lazy;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// let items = vec![1, 2, 3, 4];
+/// let sum = AtomicUsize::new(0);
+/// fibers_global::scope(|s| {
+///     for chunk in items.chunks(2) {
+///         let sum = &sum;
+///         s.spawn(lazy(move || {
+///             sum.fetch_add(chunk.iter().sum(), Ordering::SeqCst);
+///             Ok(())
+///         }));
+///     }
+/// });
+/// assert_eq!(sum.into_inner(), 10);
+/// ```
+pub fn scope<'a, F, R>(f: F) -> R
+where
+    F: FnOnce(&Scope<'a>) -> R,
+{
+    let _guard = BlockingGuard::new();
+    let scope = Scope {
+        pending: Arc::new(Pending::default()),
+        _invariant: PhantomData,
+    };
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+    scope.pending.wait();
+    result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+/// A scope for spawning fibers that borrow data living for `'a` (see [`scope`]).
+///
+/// [`scope`]: ./fn.scope.html
+#[derive(Debug)]
+pub struct Scope<'a> {
+    pending: Arc<Pending>,
+
+    // Invariant in `'a` so that fibers cannot borrow data shorter-lived than the scope.
+    _invariant: PhantomData<&'a mut &'a ()>,
+}
+impl<'a> Scope<'a> {
+    /// Spawns a fiber by using the global `ThreadPoolExecutor`.
+    ///
+    /// Unlike `spawn`, the future may borrow data living for `'a`.
+    pub fn spawn<F>(&self, future: F)
+    where
+        F: Future<Item = (), Error = ()> + Send + 'a,
+    {
+        let future: Box<dyn Future<Item = (), Error = ()> + Send + 'a> = Box::new(future);
+
+        // SAFETY: `scope` does not return until this fiber is dropped (see `ScopedFiber::drop`),
+        // and the data borrowed by the future outlives `scope` since `'a` is invariant.
+        let future: Box<dyn Future<Item = (), Error = ()> + Send + 'static> =
+            unsafe { mem::transmute(future) };
+
+        self.pending.increment();
+        let future = panic_handler::wrap(ScopedFiber {
+            future: Some(future),
+            pending: Arc::clone(&self.pending),
+        });
+        #[cfg(feature = "tracing")]
+        let future = crate::trace::instrument(crate::FiberId::next(), future);
+
+        // Not via `spawn`, which drops the future during `drain_and_shutdown`.
+        with_spawn_handle(future, |h, f| h.spawn(f));
+    }
+}
+
+/// The number of the fibers of a scope that have not been dropped yet.
+#[derive(Debug, Default)]
+struct Pending {
+    count: Mutex<usize>,
+    zero: Condvar,
+}
+impl Pending {
+    fn increment(&self) {
+        *self.count.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+    }
+
+    fn decrement(&self) {
+        let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        *count -= 1;
+        if *count == 0 {
+            self.zero.notify_all();
+        }
+    }
+
+    #[cfg(not(feature = "no-threads"))]
+    fn wait(&self) {
+        let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        while *count != 0 {
+            count = self
+                .zero
+                .wait(count)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Runs the thread-local executor until the count reaches zero.
+    #[cfg(feature = "no-threads")]
+    fn wait(&self) {
+        while *self.count.lock().unwrap_or_else(PoisonError::into_inner) != 0 {
+            if !crate::waiter::park(None) {
+                // Returning here would let the fibers outlive the data they borrow.
+                eprintln!(
+                    "`fibers_global::scope` cannot wait for its fibers inside a fiber with the `no-threads` feature"
+                );
+                std::process::abort();
+            }
+        }
+    }
+}
+
+struct ScopedFiber {
+    future: Option<Box<dyn Future<Item = (), Error = ()> + Send>>,
+    pending: Arc<Pending>,
+}
+impl Future for ScopedFiber {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        self.future.as_mut().expect("Never fails").poll()
+    }
+}
+impl Drop for ScopedFiber {
+    fn drop(&mut self) {
+        // The borrowing future must be dropped before the scope is notified.
+        drop(self.future.take());
+        self.pending.decrement();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fibers::time::timer;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn scope_works() {
+        let items = (0..100).collect::<Vec<usize>>();
+        let sum = AtomicUsize::new(0);
+        let spawned = scope(|s| {
+            for chunk in items.chunks(10) {
+                let sum = &sum;
+                s.spawn(timer::timeout(Duration::from_millis(5)).then(move |_| {
+                    sum.fetch_add(chunk.iter().sum(), Ordering::SeqCst);
+                    Ok(())
+                }));
+            }
+            items.len() / 10
+        });
+        assert_eq!(spawned, 10);
+        assert_eq!(sum.load(Ordering::SeqCst), 4950);
+
+        // Nothing is spawned.
+        assert_eq!(scope(|_| 1), 1);
+    }
+
+    #[test]
+    fn scope_waits_fibers_even_if_panicked() {
+        let items = [1, 2, 3];
+        let sum = AtomicUsize::new(0);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            scope(|s| {
+                let (items, sum) = (&items, &sum);
+                s.spawn(timer::timeout(Duration::from_millis(10)).then(move |_| {
+                    sum.fetch_add(items.iter().sum(), Ordering::SeqCst);
+                    Ok(())
+                }));
+                panic!("scope");
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(sum.load(Ordering::SeqCst), 6);
+    }
+}
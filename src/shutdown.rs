@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+/// Returns a guard that drains and shuts down the global `ThreadPoolExecutor` when it is dropped.
+///
+/// The background thread of the global executor is never joined by default,
+/// so the fibers still running when `main` returns are killed abruptly with the process.
+/// Holding the returned guard in `main` makes the cleanup deterministic:
+/// dropping it calls [`drain_and_shutdown`] with `timeout`.
+///
+/// This is opt-in: nothing is registered unless this function is called.
+///
+/// [`drain_and_shutdown`]: ./fn.drain_and_shutdown.html
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let _guard = fibers_global::install_atexit_drain(Duration::from_secs(10));
+///     fibers_global::spawn(futures::lazy(|| Ok(())));
+///
+///     // The fiber above is completed before exiting.
+/// }
+/// ```
+pub fn install_atexit_drain(timeout: Duration) -> ShutdownGuard {
+    ShutdownGuard { timeout }
+}
+
+/// A guard returned by [`install_atexit_drain`].
+///
+/// [`install_atexit_drain`]: ./fn.install_atexit_drain.html
+#[derive(Debug)]
+#[must_use = "the executor is drained when the guard is dropped"]
+pub struct ShutdownGuard {
+    timeout: Duration,
+}
+impl ShutdownGuard {
+    /// Returns the timeout of the drain.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+impl Drop for ShutdownGuard {
+    fn drop(&mut self) {
+        crate::drain_and_shutdown(self.timeout);
+    }
+}
@@ -0,0 +1,185 @@
+use fibers::fiber::{self, Unpark};
+use fibers::sync::oneshot::Monitor;
+use futures::{Async, Poll};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Wake, Waker};
+
+/// Spawns a fiber to execute the given `std::future::Future` by using the global `ThreadPoolExecutor`.
+pub fn spawn_std<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    crate::spawn(Compat::new(async move {
+        future.await;
+        Ok(())
+    }));
+}
+
+/// Spawns a fiber to execute the given `std::future::Future` by using the global `ThreadPoolExecutor`
+/// and returns a future to monitor it's execution result.
+pub fn spawn_monitor_std<F, T, E>(future: F) -> Monitor<T, E>
+where
+    F: Future<Output = Result<T, E>> + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    crate::spawn_monitor(Compat::new(future))
+}
+
+/// Executes the given `std::future::Future` by using the global `ThreadPoolExecutor` and waits the result.
+///
+/// Like `execute`, this function panics if the global executor aborted.
+///
+/// # Examples
+///
+/// ```
+/// let result = fibers_global::execute_std(async { Ok::<_, ()>(1 + 2) });
+/// assert_eq!(result, Ok(3));
+/// ```
+pub fn execute_std<F, T, E>(future: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>> + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    crate::execute(Compat::new(future))
+}
+
+/// An adapter that makes a `std::future::Future` executable as a fiber.
+struct Compat<F> {
+    future: Pin<Box<F>>,
+    waker: Arc<FiberWaker>,
+}
+impl<F> Compat<F> {
+    fn new(future: F) -> Self {
+        Compat {
+            future: Box::pin(future),
+            waker: Arc::new(FiberWaker {
+                notified: AtomicBool::new(false),
+                unpark: Mutex::new(None),
+            }),
+        }
+    }
+}
+impl<F, T, E> futures::Future for Compat<F>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    type Item = T;
+    type Error = E;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        self.waker.notified.store(false, Ordering::SeqCst);
+        let waker = Waker::from(Arc::clone(&self.waker));
+        let mut context = Context::from_waker(&waker);
+        if let std::task::Poll::Ready(result) = self.future.as_mut().poll(&mut context) {
+            return result.map(Async::Ready);
+        }
+
+        *self.waker.lock() = fiber::with_current_context(|mut c| c.park());
+        if self.waker.notified.swap(false, Ordering::SeqCst) {
+            // Woken up while polling.
+            self.waker.lock().take();
+        }
+        Ok(Async::NotReady)
+    }
+}
+
+/// A `Waker` that unparks the fiber polling the future.
+struct FiberWaker {
+    notified: AtomicBool,
+    unpark: Mutex<Option<Unpark>>,
+}
+impl FiberWaker {
+    fn lock(&self) -> MutexGuard<'_, Option<Unpark>> {
+        self.unpark.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+impl Wake for FiberWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.notified.store(true, Ordering::SeqCst);
+
+        // Dropping `Unpark` unparks the fiber.
+        self.lock().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fibers::sync::oneshot;
+    use futures::Future as _;
+    #[cfg(not(feature = "no-threads"))]
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::*;
+
+    /// A future that becomes ready once it has been woken up by another thread.
+    struct WokenByThread {
+        woken: Arc<AtomicBool>,
+    }
+    impl Future for WokenByThread {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context) -> std::task::Poll<()> {
+            if self.woken.load(Ordering::SeqCst) {
+                return std::task::Poll::Ready(());
+            }
+            let woken = Arc::clone(&self.woken);
+            let waker = cx.waker().clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(10));
+                woken.store(true, Ordering::SeqCst);
+                waker.wake();
+            });
+            std::task::Poll::Pending
+        }
+    }
+
+    #[test]
+    fn execute_std_works() {
+        let result = execute_std(async {
+            WokenByThread {
+                woken: Arc::default(),
+            }
+            .await;
+            Ok::<_, ()>(1)
+        });
+        assert_eq!(result, Ok(1));
+
+        let result = execute_std(async { Err::<(), _>("failed") });
+        assert_eq!(result, Err("failed"));
+    }
+
+    #[cfg(not(feature = "no-threads"))]
+    #[test]
+    fn spawn_std_works() {
+        let (tx, rx) = mpsc::channel();
+        spawn_std(async move {
+            WokenByThread {
+                woken: Arc::default(),
+            }
+            .await;
+            let _ = tx.send(());
+        });
+        assert!(rx.recv_timeout(Duration::from_secs(10)).is_ok());
+    }
+
+    #[test]
+    fn spawn_monitor_std_works() {
+        let (tx, rx) = oneshot::channel();
+        let monitor = spawn_monitor_std(async move { Ok::<_, ()>(2) });
+        crate::spawn(monitor.then(move |r| {
+            let _ = tx.send(r.ok());
+            Ok(())
+        }));
+        assert_eq!(crate::execute(rx), Ok(Some(2)));
+    }
+}
@@ -0,0 +1,119 @@
+use futures::{IntoFuture, Stream};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use crate::{execute, spawn, spawn_fn};
+
+/// Executes the given stream by using the global `ThreadPoolExecutor` and waits all the items.
+///
+/// If the stream fails, the error is returned as soon as it is produced
+/// (the items received before that are discarded).
+///
+/// Note that this function blocks forever if `stream` is infinite and never fails.
+///
+/// # Examples
+///
+/// ```
+/// use futures::stream;
+///
+/// let items = fibers_global::execute_stream(stream::iter_ok::<_, ()>(vec![1, 2, 3]));
+/// assert_eq!(items, Ok(vec![1, 2, 3]));
+/// ```
+pub fn execute_stream<S>(stream: S) -> Result<Vec<S::Item>, S::Error>
+where
+    S: Stream + Send + 'static,
+    S::Item: Send + 'static,
+    S::Error: Send + 'static,
+{
+    execute(stream.collect())
+}
+
+/// Spawns a fiber to drain the given stream by using the global `ThreadPoolExecutor`.
+///
+/// The items of the stream are discarded, so this is useful for streams executed for their side effects.
+/// The fiber exits when the stream terminates or fails.
+pub fn spawn_stream<S>(stream: S)
+where
+    S: Stream<Error = ()> + Send + 'static,
+    S::Item: Send + 'static,
+{
+    spawn(stream.for_each(|_| Ok(())));
+}
+
+/// Spawns a fiber that sends values to the returned receiver by using the global `ThreadPoolExecutor`.
+///
+/// `f` is called on a worker thread of the executor with the sender,
+/// and the returned future is executed as the fiber.
+/// This bridges producers on the executor and synchronous consumers.
+///
+/// After the receiver is dropped, sending to the sender fails,
+/// so the fiber should check the result of `Sender::send` to exit.
+///
+/// # Examples
+///
+/// ```
+/// # if cfg!(feature = "no-threads") { return; }
+/// let rx = fibers_global::spawn_with_channel(|tx| {
+///     for i in 0..3 {
+///         let _ = tx.send(i);
+///     }
+///     Ok(())
+/// });
+/// assert_eq!(rx.iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+/// ```
+pub fn spawn_with_channel<F, T, R>(f: F) -> Receiver<T>
+where
+    F: FnOnce(Sender<T>) -> R + Send + 'static,
+    T: Send + 'static,
+    R: IntoFuture<Item = (), Error = ()> + 'static,
+    R::Future: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    spawn_fn(move || f(tx));
+    rx
+}
+
+#[cfg(all(test, not(feature = "no-threads")))]
+mod tests {
+    use fibers::time::timer;
+    use futures::{stream, Future};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn execute_stream_works() {
+        let items = execute_stream(stream::iter_ok::<_, ()>(0..5));
+        assert_eq!(items, Ok(vec![0, 1, 2, 3, 4]));
+
+        // An infinite stream that fails in the middle.
+        let stream = stream::iter_result((0..).map(|i| if i == 3 { Err(i) } else { Ok(i) }));
+        assert_eq!(execute_stream(stream), Err(3));
+    }
+
+    #[test]
+    fn spawn_stream_works() {
+        let (tx, rx) = mpsc::channel();
+        let items = (0..5).map(|i| if i == 3 { Err(()) } else { Ok(i) });
+        spawn_stream(stream::iter_result(items).map(move |i| {
+            let _ = tx.send(i);
+        }));
+
+        // The stream is drained until it fails, then the fiber (holding `tx`) is dropped.
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn spawn_with_channel_stops_after_receiver_is_dropped() {
+        let (exited_tx, exited_rx) = mpsc::channel();
+        let rx = spawn_with_channel(move |tx| {
+            stream::repeat(())
+                .and_then(|()| timer::timeout(Duration::from_millis(1)).map_err(|_| ()))
+                .fold(0, move |i, ()| tx.send(i).map(|()| i + 1).map_err(|_| ()))
+                .then(move |_| exited_tx.send(()).map_err(|_| ()))
+        });
+        assert_eq!(rx.iter().take(3).collect::<Vec<_>>(), vec![0, 1, 2]);
+
+        drop(rx);
+        assert!(exited_rx.recv_timeout(Duration::from_secs(10)).is_ok());
+    }
+}
@@ -0,0 +1,32 @@
+use futures::{Future, Poll};
+use tracing::Span;
+
+use crate::fiber_id::{FiberId, WithFiberId};
+
+/// Wraps `future` in a `fiber` span carrying `id`, which is entered on each poll.
+///
+/// The identifier is also returned by `current_fiber_id` while the future is polled.
+///
+/// This is applied by every function spawning fibers on the global executor except for
+/// the `execute` family (see the documentation of `spawn`).
+pub(crate) fn instrument<F: Future>(id: FiberId, future: F) -> Instrumented<WithFiberId<F>> {
+    Instrumented {
+        span: tracing::info_span!("fiber", id = id.as_u64()),
+        future: WithFiberId::new(id, future),
+    }
+}
+
+/// A future that enters `span` while it is polled.
+pub(crate) struct Instrumented<F> {
+    span: Span,
+    future: F,
+}
+impl<F: Future> Future for Instrumented<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let _enter = self.span.enter();
+        self.future.poll()
+    }
+}
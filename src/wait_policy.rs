@@ -0,0 +1,120 @@
+use futures::Future;
+use std::time::Duration;
+
+use crate::metrics::BlockingGuard;
+use crate::waiter::Waiter;
+use crate::{into_execute_result, with_spawn_handle};
+
+/// A policy describing how `execute_with_policy` waits for the fiber.
+///
+/// The calling thread first yields to the OS scheduler `yields` times (re-polling the fiber in between),
+/// and then parks until the fiber wakes it up, re-polling at least every `park_timeout` if specified.
+///
+/// Yielding lets a blocked caller cooperate with the worker threads when the pool is saturated,
+/// while parking avoids burning CPU during long waits.
+///
+/// # Examples
+///
+/// ```
+/// use fibers_global::WaitPolicy;
+/// use std::time::Duration;
+///
+/// let policy = WaitPolicy::yield_then_park(16, Some(Duration::from_millis(1)));
+/// assert_eq!(policy.yields(), 16);
+/// assert_eq!(policy.park_timeout(), Some(Duration::from_millis(1)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitPolicy {
+    yields: usize,
+    park_timeout: Option<Duration>,
+}
+impl WaitPolicy {
+    /// Makes a new `WaitPolicy` that only parks until the fiber wakes the calling thread up.
+    ///
+    /// This is the policy used by `execute`.
+    pub fn park() -> Self {
+        WaitPolicy {
+            yields: 0,
+            park_timeout: None,
+        }
+    }
+
+    /// Makes a new `WaitPolicy` that yields `yields` times before parking.
+    ///
+    /// If `park_timeout` is `Some`, parking times out and the fiber is re-polled at that interval.
+    pub fn yield_then_park(yields: usize, park_timeout: Option<Duration>) -> Self {
+        WaitPolicy {
+            yields,
+            park_timeout,
+        }
+    }
+
+    /// Returns the number of times the calling thread yields before parking.
+    pub fn yields(&self) -> usize {
+        self.yields
+    }
+
+    /// Returns the timeout of each parking.
+    pub fn park_timeout(&self) -> Option<Duration> {
+        self.park_timeout
+    }
+}
+impl Default for WaitPolicy {
+    fn default() -> Self {
+        Self::park()
+    }
+}
+
+/// Executes the given future by using the global `ThreadPoolExecutor` and waits the result as described by `policy`.
+///
+/// This is the same as [`execute`] except for how the calling thread waits for the fiber.
+///
+/// [`execute`]: ./fn.execute.html
+///
+/// # Examples
+///
+/// ```
+/// use fibers_global::WaitPolicy;
+/// use futures::future::ok;
+///
+/// let policy = WaitPolicy::yield_then_park(8, None);
+/// assert_eq!(fibers_global::execute_with_policy(ok::<_, ()>(1), policy), Ok(1));
+/// ```
+pub fn execute_with_policy<F>(future: F, policy: WaitPolicy) -> Result<F::Item, F::Error>
+where
+    F: Future + Send + 'static,
+    F::Item: Send + 'static,
+    F::Error: Send + 'static,
+{
+    let _guard = BlockingGuard::new();
+    let result = with_spawn_handle(future, Waiter::spawn).wait_with_policy(policy);
+    into_execute_result(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use fibers::time::timer;
+    use futures::Future;
+
+    use super::*;
+
+    #[test]
+    fn execute_with_policy_works() {
+        let policies = [
+            WaitPolicy::default(),
+            WaitPolicy::yield_then_park(4, None),
+            WaitPolicy::yield_then_park(4, Some(Duration::from_millis(1))),
+            WaitPolicy::yield_then_park(usize::MAX, None),
+        ];
+        for (i, policy) in policies.iter().enumerate() {
+            let future = timer::timeout(Duration::from_millis(5)).map(move |()| i);
+            assert_eq!(execute_with_policy(future, *policy), Ok(i));
+        }
+
+        let future = timer::timeout(Duration::from_millis(1)).then(|_| Err::<(), _>("failed"));
+        assert_eq!(
+            execute_with_policy(future, WaitPolicy::park()),
+            Err("failed")
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use fibers::sync::oneshot::{self, Monitor, MonitorError, Monitored};
+use fibers::Spawn;
+use futures::{Async, Future, Poll};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use crate::WaitPolicy;
+
+/// A handle to wait for the result of a fiber from outside of the executor.
+///
+/// `Monitor` does not notify tasks outside of fibers,
+/// so the waiting thread is woken up by the fiber itself.
+///
+/// If this is dropped before the fiber exits, the fiber is abandoned (i.e., dropped at its next poll).
+pub(crate) struct Waiter<T, E> {
+    monitor: Monitor<T, E>,
+    polled: Arc<AtomicBool>,
+    _abandon: oneshot::Sender<()>,
+}
+impl<T, E> Waiter<T, E>
+where
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    pub fn spawn<H, F>(handle: &H, future: F) -> Self
+    where
+        H: Spawn,
+        F: Future<Item = T, Error = E> + Send + 'static,
+    {
+        let (monitored, monitor) = oneshot::monitor();
+        let (abandon_tx, abandon_rx) = oneshot::channel();
+        let polled = Arc::new(AtomicBool::new(false));
+        let unparker = Unparker {
+            monitored: Some(monitored),
+            polled: Arc::clone(&polled),
+            waiter: thread::current(),
+        };
+        handle.spawn(WaitableFiber {
+            future,
+            unparker,
+            abandoned: abandon_rx,
+        });
+        Waiter {
+            monitor,
+            polled,
+            _abandon: abandon_tx,
+        }
+    }
+
+    /// Blocks the current thread until the fiber exits.
+    pub fn wait(&mut self) -> Result<T, MonitorError<E>> {
+        loop {
+            if let Some(result) = self.poll_once() {
+                return result;
+            }
+            if !park(None) {
+                return Err(MonitorError::Aborted);
+            }
+        }
+    }
+
+    /// Blocks the current thread until the fiber exits, re-polling at least every `interval`.
+    ///
+    /// A zero `interval` makes this spin without parking.
+    pub fn wait_with_interval(&mut self, interval: Duration) -> Result<T, MonitorError<E>> {
+        loop {
+            if let Some(result) = self.poll_once() {
+                return result;
+            }
+            if !park(Some(interval)) {
+                return Err(MonitorError::Aborted);
+            }
+        }
+    }
+
+    /// Blocks the current thread until the fiber exits, calling `progress` before each parking
+    /// that lasts at most `interval`.
+    pub fn wait_with_progress<P>(
+        &mut self,
+        interval: Duration,
+        mut progress: P,
+    ) -> Result<T, MonitorError<E>>
+    where
+        P: FnMut(),
+    {
+        loop {
+            if let Some(result) = self.poll_once() {
+                return result;
+            }
+            progress();
+            if !park(Some(interval)) {
+                return Err(MonitorError::Aborted);
+            }
+        }
+    }
+
+    /// Blocks the current thread until the fiber exits, waiting as described by `policy`.
+    pub fn wait_with_policy(&mut self, policy: WaitPolicy) -> Result<T, MonitorError<E>> {
+        for _ in 0..policy.yields() {
+            if let Some(result) = self.poll_once() {
+                return result;
+            }
+            if !yield_now() {
+                return Err(MonitorError::Aborted);
+            }
+        }
+        match policy.park_timeout() {
+            None => self.wait(),
+            Some(timeout) => self.wait_with_interval(timeout),
+        }
+    }
+
+    /// Blocks the current thread until the fiber exits or `deadline` is reached.
+    ///
+    /// The future is polled at least once even if `deadline` has already passed,
+    /// so this does not time out before the fiber has been scheduled.
+    pub fn wait_until(&mut self, deadline: Instant) -> Option<Result<T, MonitorError<E>>> {
+        loop {
+            if let Some(result) = self.poll_once() {
+                return Some(result);
+            }
+            if !self.polled.load(Ordering::SeqCst) {
+                // The fiber unparks this thread after its first poll.
+                if !park(None) {
+                    return Some(Err(MonitorError::Aborted));
+                }
+                continue;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            if !park(Some(deadline - now)) {
+                return Some(Err(MonitorError::Aborted));
+            }
+        }
+    }
+
+    fn poll_once(&mut self) -> Option<Result<T, MonitorError<E>>> {
+        match self.monitor.poll() {
+            Err(e) => Some(Err(e)),
+            Ok(Async::Ready(v)) => Some(Ok(v)),
+            Ok(Async::NotReady) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The number of times `park` has returned on the current thread (for testing).
+    pub(crate) static PARKS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Parks the current thread at most `timeout` (or until unparked if `None`), and returns `true`.
+///
+/// With the `no-threads` feature, this runs the thread-local executor once instead (see `no_threads::run_once`),
+/// so the fibers spawned by the current thread make progress while it waits.
+/// In that case `false` is returned if the executor cannot be run (i.e., this is called inside a fiber).
+pub(crate) fn park(timeout: Option<Duration>) -> bool {
+    #[cfg(feature = "no-threads")]
+    {
+        let _ = timeout;
+        crate::no_threads::run_once()
+    }
+    #[cfg(not(feature = "no-threads"))]
+    {
+        match timeout {
+            None => thread::park(),
+            Some(timeout) => thread::park_timeout(timeout),
+        }
+        #[cfg(test)]
+        PARKS.with(|parks| parks.set(parks.get() + 1));
+        true
+    }
+}
+
+/// Same as `park` except that this sleeps for `duration` instead of parking.
+pub(crate) fn sleep(duration: Duration) -> bool {
+    #[cfg(feature = "no-threads")]
+    {
+        let _ = duration;
+        crate::no_threads::run_once()
+    }
+    #[cfg(not(feature = "no-threads"))]
+    {
+        thread::sleep(duration);
+        true
+    }
+}
+
+/// Same as `park` except that this yields the current thread instead of parking.
+fn yield_now() -> bool {
+    #[cfg(feature = "no-threads")]
+    {
+        crate::no_threads::run_once()
+    }
+    #[cfg(not(feature = "no-threads"))]
+    {
+        thread::yield_now();
+        true
+    }
+}
+
+struct WaitableFiber<F: Future> {
+    future: F,
+    unparker: Unparker<F::Item, F::Error>,
+    abandoned: oneshot::Receiver<()>,
+}
+impl<F: Future> Future for WaitableFiber<F> {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match self.abandoned.poll() {
+            Ok(Async::NotReady) => {}
+            _ => {
+                // The waiting side has gone away.
+                return Ok(Async::Ready(()));
+            }
+        }
+
+        let result = match self.future.poll() {
+            Ok(Async::NotReady) => {
+                self.unparker.polled();
+                return Ok(Async::NotReady);
+            }
+            Ok(Async::Ready(v)) => Ok(v),
+            Err(e) => Err(e),
+        };
+        self.unparker.exit(result);
+        Ok(Async::Ready(()))
+    }
+}
+
+/// Unparks the waiting thread after the monitored result has been sent.
+///
+/// If the fiber is dropped before exiting (e.g., the executor aborted),
+/// dropping this also unparks the thread so that it can observe the disconnection.
+struct Unparker<T, E> {
+    monitored: Option<Monitored<T, E>>,
+    polled: Arc<AtomicBool>,
+    waiter: Thread,
+}
+impl<T, E> Unparker<T, E> {
+    /// Notifies the waiting thread that the future has been polled (unless already notified).
+    fn polled(&mut self) {
+        if !self.polled.swap(true, Ordering::SeqCst) {
+            self.waiter.unpark();
+        }
+    }
+
+    fn exit(&mut self, result: Result<T, E>) {
+        if let Some(monitored) = self.monitored.take() {
+            monitored.exit(result);
+        }
+        self.waiter.unpark();
+    }
+}
+impl<T, E> Drop for Unparker<T, E> {
+    fn drop(&mut self) {
+        let _ = self.monitored.take();
+        self.waiter.unpark();
+    }
+}
@@ -0,0 +1,309 @@
+use fibers::sync::oneshot;
+use futures::future::{self, lazy};
+use futures::Future;
+use std::cell::Cell;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, PoisonError};
+
+use crate::global::with_global_executor;
+use crate::{metrics, panic_handler, InvalidWorkerError};
+
+thread_local! {
+    static ON_POOL_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Returns `true` if the current thread is one of the scheduler threads of the global `ThreadPoolExecutor`.
+///
+/// Blocking in `execute` (or its variants) on a scheduler thread stalls the worker,
+/// and may deadlock if the awaited fibers need the worker to make progress.
+/// This function helps to detect such misuse.
+///
+/// With the `no-threads` feature, this returns `true` while the current thread is running a fiber (e.g., of its thread-local executor).
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::lazy;
+///
+/// assert!(!fibers_global::on_pool_thread());
+/// assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(fibers_global::on_pool_thread()))), Ok(true));
+/// ```
+pub fn on_pool_thread() -> bool {
+    #[cfg(feature = "no-threads")]
+    {
+        crate::no_threads::is_running()
+    }
+    #[cfg(not(feature = "no-threads"))]
+    ON_POOL_THREAD.with(Cell::get)
+}
+
+/// Marks the current thread as a scheduler thread of the global executor.
+pub(crate) fn mark_pool_thread() {
+    ON_POOL_THREAD.with(|on| on.set(true));
+}
+
+/// Checks that the current thread is not going to block on a scheduler thread (see `on_pool_thread`).
+///
+/// This panics in debug builds and only warns in release builds.
+///
+/// With the `no-threads` feature, the waits inside fibers fail instead (see `waiter::park`), so this checks nothing.
+pub(crate) fn check_not_on_pool_thread() {
+    let on_pool_thread = ON_POOL_THREAD.with(Cell::get);
+    debug_assert!(!on_pool_thread, "execute called from a pool worker thread");
+    if !on_pool_thread {
+        return;
+    }
+    crate::global::report_warning(format_args!(
+        "`fibers_global::execute` (or its variant) is called on a scheduler thread of the global executor; it may deadlock"
+    ));
+}
+
+/// Spawns a fiber on the `worker`-th scheduler thread of the global `ThreadPoolExecutor`.
+///
+/// `worker` must be less than [`thread_count`], otherwise `Err(InvalidWorkerError)` is returned.
+///
+/// `fibers` does not support per-worker spawning, so the future is polled by the fiber that
+/// resides on the worker since the executor started (which also names the thread `"{prefix}-{worker}"`).
+/// This means that:
+///
+/// - All the futures pinned to the same worker are re-polled whenever one of them is woken up.
+///   So this is intended for experiments (e.g., cache affinity) rather than for a large number of fibers.
+/// - If the executor is restarted after an abort, the remaining pinned futures are dropped like other fibers.
+///
+/// With the `no-threads` feature, the only worker is the current thread (i.e., `spawn_on(0, future)` is `spawn(future)`).
+///
+/// [`thread_count`]: ./fn.thread_count.html
+///
+/// # Examples
+///
+/// ```
+/// use futures::future::lazy;
+///
+/// assert!(fibers_global::spawn_on(0, lazy(|| Ok(()))).is_ok());
+///
+/// let worker = fibers_global::thread_count();
+/// assert!(fibers_global::spawn_on(worker, lazy(|| Ok(()))).is_err());
+/// ```
+pub fn spawn_on<F>(worker: usize, future: F) -> Result<(), InvalidWorkerError>
+where
+    F: Future<Item = (), Error = ()> + Send + 'static,
+{
+    let future = panic_handler::wrap(future);
+    #[cfg(feature = "tracing")]
+    let future = crate::trace::instrument(crate::FiberId::next(), future);
+    #[cfg(feature = "no-threads")]
+    {
+        if worker != 0 {
+            return Err(InvalidWorkerError {
+                worker,
+                thread_count: 1,
+            });
+        }
+        let future = metrics::track(future);
+        crate::no_threads::with_handle(|h| fibers::Spawn::spawn(h, future));
+        Ok(())
+    }
+    #[cfg(not(feature = "no-threads"))]
+    with_global_executor(|executor| {
+        let sender = executor.workers.get(worker).ok_or(InvalidWorkerError {
+            worker,
+            thread_count: executor.thread_count,
+        })?;
+        let future = metrics::track(future);
+        let _ = sender.send(Box::new(future));
+        Ok(())
+    })
+}
+
+/// Starts the global `ThreadPoolExecutor` eagerly and waits until all of its scheduler threads are running.
+///
+/// The executor is started lazily, so the first `spawn` or `execute` pays the cost of spawning the threads.
+/// Calling this early (e.g., at the beginning of `main` or before a benchmark) moves the cost out of the hot path.
+///
+/// Each scheduler thread is confirmed by a round trip of a no-op future pinned to it (see [`spawn_on`]).
+/// This is idempotent: once the threads have been confirmed, subsequent calls only take the lock of the executor.
+///
+/// With the `no-threads` feature, this does nothing.
+///
+/// [`spawn_on`]: ./fn.spawn_on.html
+///
+/// # Examples
+///
+/// ```
+/// fibers_global::warm_up();
+/// # if cfg!(feature = "no-threads") { return; }
+/// assert!(fibers_global::is_started());
+/// ```
+pub fn warm_up() {
+    if cfg!(feature = "no-threads") {
+        return;
+    }
+    let workers = with_global_executor(|executor| {
+        if executor.warmed_up.load(Ordering::SeqCst) {
+            None
+        } else {
+            Some(executor.workers.clone())
+        }
+    });
+    let workers = match workers {
+        None => return,
+        Some(workers) => workers,
+    };
+    let acks = workers
+        .iter()
+        .map(|worker| {
+            let (tx, rx) = oneshot::channel();
+            let _ = worker.send(Box::new(lazy(move || {
+                let _ = tx.send(());
+                Ok(())
+            })));
+            rx
+        })
+        .collect::<Vec<_>>();
+    if crate::execute(future::join_all(acks)).is_ok() {
+        with_global_executor(|executor| executor.warmed_up.store(true, Ordering::SeqCst));
+    }
+}
+
+/// Returns the ids of the scheduler threads of the global `ThreadPoolExecutor`, indexed by the workers.
+///
+/// This is intended for integration with external tools (e.g., filtering the samples of a profiler).
+/// On Linux, the ids are the native thread ids assigned by the OS (i.e., `gettid(2)`, shown as `TID` by `top -H`).
+/// On the other platforms, they are opaque values derived from `std::thread::ThreadId`,
+/// which are unique among the threads of the process but unrelated to the OS.
+///
+/// The ids are recorded when the threads start, so this calls [`warm_up`] to wait for them.
+/// If the executor is restarted after an abort, the ids of the new threads are returned.
+///
+/// With the `no-threads` feature, the id of the current thread is returned.
+///
+/// [`warm_up`]: ./fn.warm_up.html
+///
+/// # Examples
+///
+/// ```
+/// let ids = fibers_global::worker_thread_ids();
+/// assert_eq!(ids.len(), fibers_global::thread_count());
+/// ```
+pub fn worker_thread_ids() -> Vec<u64> {
+    if cfg!(feature = "no-threads") {
+        return vec![crate::global::native_thread_id()];
+    }
+    warm_up();
+    let thread_ids = with_global_executor(|executor| Arc::clone(&executor.thread_ids));
+    let thread_ids = thread_ids.lock().unwrap_or_else(PoisonError::into_inner);
+    thread_ids.iter().filter_map(|id| *id).collect()
+}
+
+#[cfg(all(test, not(feature = "no-threads")))]
+mod tests {
+    use fibers::sync::oneshot;
+    use fibers::time::timer;
+    use futures::future::lazy;
+    use futures::Future;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::execute;
+    #[cfg(target_os = "linux")]
+    use crate::global::tests::os_thread_name;
+
+    #[test]
+    fn on_pool_thread_works() {
+        assert!(!on_pool_thread());
+
+        let (tx, rx) = oneshot::channel();
+        crate::spawn(lazy(move || {
+            let _ = tx.send(on_pool_thread());
+            Ok(())
+        }));
+        assert_eq!(execute(rx), Ok(true));
+
+        let (tx, rx) = oneshot::channel();
+        spawn_on(
+            0,
+            lazy(move || {
+                let _ = tx.send(on_pool_thread());
+                Ok(())
+            }),
+        )
+        .unwrap();
+        assert_eq!(execute(rx), Ok(true));
+
+        // Threads spawned by `spawn_blocking` are not scheduler threads.
+        assert_eq!(
+            execute(crate::spawn_blocking(on_pool_thread)).ok(),
+            Some(false)
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "execute called from a pool worker thread")]
+    fn execute_on_pool_thread_panics() {
+        let (tx, rx) = oneshot::channel();
+        crate::spawn(lazy(move || {
+            let result = std::panic::catch_unwind(|| execute(lazy(|| Ok::<_, ()>(()))));
+            let _ = tx.send(result.err());
+            Ok(())
+        }));
+        if let Some(payload) = execute(rx).unwrap() {
+            std::panic::resume_unwind(payload);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn spawn_on_works() {
+        let (tx, rx) = oneshot::channel();
+        spawn_on(
+            0,
+            lazy(move || {
+                let _ = tx.send(os_thread_name());
+                Ok(())
+            }),
+        )
+        .unwrap();
+        assert_eq!(execute(rx).unwrap(), "fibers-global-0");
+
+        // Pinned futures can wait for other events.
+        let (tx, rx) = oneshot::channel();
+        let future = timer::timeout(Duration::from_millis(5)).then(move |_| {
+            let _ = tx.send(os_thread_name());
+            Ok(())
+        });
+        spawn_on(0, future).unwrap();
+        assert_eq!(execute(rx).unwrap(), "fibers-global-0");
+
+        let thread_count = crate::thread_count();
+        assert_eq!(
+            spawn_on(thread_count, lazy(|| Ok(()))),
+            Err(InvalidWorkerError {
+                worker: thread_count,
+                thread_count
+            })
+        );
+    }
+
+    #[test]
+    fn worker_thread_ids_works() {
+        let ids = worker_thread_ids();
+        assert_eq!(ids.len(), crate::thread_count());
+
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), ids.len());
+
+        let (tx, rx) = oneshot::channel();
+        spawn_on(
+            0,
+            lazy(move || {
+                let _ = tx.send(crate::global::native_thread_id());
+                Ok(())
+            }),
+        )
+        .unwrap();
+        assert_eq!(execute(rx), Ok(ids[0]));
+    }
+}
@@ -0,0 +1,28 @@
+use futures::lazy;
+use std::time::Duration;
+
+#[test]
+fn blocking_callers_works() {
+    assert_eq!(fibers_global::blocking_callers(), 0);
+
+    let observed = fibers_global::execute(lazy(|| Ok::<_, ()>(fibers_global::blocking_callers())));
+    assert_eq!(observed, Ok(1));
+    assert_eq!(fibers_global::blocking_callers(), 0);
+
+    let observed = fibers_global::execute_timeout(
+        lazy(|| Ok::<_, ()>(fibers_global::blocking_callers())),
+        Duration::from_secs(10),
+    );
+    assert_eq!(observed, Ok(Ok(1)));
+    assert_eq!(fibers_global::blocking_callers(), 0);
+
+    // The counter is decremented even if `execute` panics.
+    let result = std::panic::catch_unwind(|| {
+        fibers_global::execute_with_poll_interval(
+            lazy(|| -> Result<(), ()> { panic!("aborted") }),
+            Duration::from_millis(1),
+        )
+    });
+    assert!(result.is_err());
+    assert_eq!(fibers_global::blocking_callers(), 0);
+}
@@ -0,0 +1,25 @@
+#![cfg(not(feature = "no-threads"))]
+
+use fibers_global::ExecutorHealth;
+use futures::lazy;
+
+#[test]
+fn config_snapshot_works() {
+    let before = fibers_global::config_snapshot();
+    assert!(!before.started);
+    assert_eq!(before.health, ExecutorHealth::NotStarted);
+    assert_eq!(before.thread_name_prefix, "fibers-global");
+    assert_eq!(before.max_restarts, 0);
+
+    assert!(fibers_global::set_thread_count(before.thread_count + 1));
+    let after = fibers_global::config_snapshot();
+    assert_eq!(after.thread_count, before.thread_count + 1);
+    assert!(!after.started);
+
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    let started = fibers_global::config_snapshot();
+    assert_eq!(started.thread_count, fibers_global::thread_count());
+    assert_eq!(started.thread_count, before.thread_count + 1);
+    assert!(started.started);
+    assert_eq!(started.health, ExecutorHealth::Running);
+}
@@ -0,0 +1,9 @@
+#![cfg(not(feature = "no-threads"))]
+
+#[test]
+fn explicit_thread_count_overrides_default() {
+    // The default never exceeds the number of logical CPUs.
+    let n = num_cpus::get() + 1;
+    assert!(fibers_global::set_thread_count(n));
+    assert_eq!(fibers_global::thread_count(), n);
+}
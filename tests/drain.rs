@@ -0,0 +1,119 @@
+use fibers::time::timer;
+use futures::{empty, lazy, Future};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+// Serializes the tests because they share the global executor.
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn lock() -> MutexGuard<'static, ()> {
+    LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[test]
+fn drain_and_shutdown_works() {
+    let _guard = lock();
+
+    let completed = Arc::new(AtomicUsize::new(0));
+    let respawned = Arc::new(AtomicBool::new(false));
+    for _ in 0..10 {
+        let completed = Arc::clone(&completed);
+        let respawned = Arc::clone(&respawned);
+        fibers_global::spawn(timer::timeout(Duration::from_millis(50)).then(move |_| {
+            assert_eq!(
+                fibers_global::checked_spawn(lazy(|| Ok(()))),
+                Err(fibers_global::SpawnError::Draining)
+            );
+
+            // Spawned after draining began, so it is dropped.
+            fibers_global::spawn(lazy(move || {
+                respawned.store(true, Ordering::SeqCst);
+                Ok(())
+            }));
+            completed.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }));
+    }
+
+    assert!(fibers_global::drain_and_shutdown(Duration::from_secs(10)));
+    assert_eq!(completed.load(Ordering::SeqCst), 10);
+    assert!(!respawned.load(Ordering::SeqCst));
+    assert!(!fibers_global::is_started());
+
+    // Spawning is accepted again.
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+}
+
+#[test]
+fn drain_and_shutdown_times_out() {
+    let _guard = lock();
+
+    fibers_global::spawn(empty());
+    assert!(!fibers_global::drain_and_shutdown(Duration::from_millis(
+        50
+    )));
+    assert!(!fibers_global::is_started());
+
+    // The remaining fiber is dropped asynchronously.
+    assert!(fibers_global::wait_for_idle(Duration::from_secs(10)));
+}
+
+#[test]
+fn scoped_monitors_are_not_affected_by_draining() {
+    let _guard = lock();
+
+    let result = Arc::new(AtomicUsize::new(0));
+    {
+        let result = Arc::clone(&result);
+        fibers_global::spawn(
+            timer::timeout(Duration::from_millis(50))
+                .then(|_| fibers_global::spawn_scoped_monitor(lazy(|| Ok::<_, ()>(1))))
+                .then(move |r| {
+                    result.store(r.unwrap_or(0), Ordering::SeqCst);
+                    Ok(())
+                }),
+        );
+    }
+
+    assert!(fibers_global::drain_and_shutdown(Duration::from_secs(10)));
+    assert_eq!(result.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(not(feature = "no-threads"))]
+#[test]
+fn scopes_are_not_affected_by_draining() {
+    use fibers::sync::oneshot;
+    use std::thread;
+    use std::time::Instant;
+
+    let _guard = lock();
+
+    // Keeps the executor draining until the scope has finished.
+    let (tx, rx) = oneshot::channel::<()>();
+    fibers_global::spawn(rx.then(|_| Ok(())));
+    let draining = thread::spawn(|| fibers_global::drain_and_shutdown(Duration::from_secs(10)));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while fibers_global::checked_spawn(lazy(|| Ok(()))) != Err(fibers_global::SpawnError::Draining)
+    {
+        assert!(Instant::now() < deadline);
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let items = [1, 2, 3];
+    let sum = AtomicUsize::new(0);
+    fibers_global::scope(|s| {
+        for item in &items {
+            let sum = &sum;
+            s.spawn(lazy(move || {
+                sum.fetch_add(*item, Ordering::SeqCst);
+                Ok(())
+            }));
+        }
+    });
+    assert_eq!(sum.load(Ordering::SeqCst), 6);
+
+    let _ = tx.send(());
+    assert!(draining.join().unwrap());
+}
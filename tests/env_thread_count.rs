@@ -0,0 +1,56 @@
+#![cfg(all(feature = "test-reset", not(feature = "no-threads")))]
+
+use std::env;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+const THREADS_ENV: &str = "FIBERS_GLOBAL_THREADS";
+
+// Serializes the tests because they share the environment variable and the global executor.
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn lock() -> MutexGuard<'static, ()> {
+    LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the thread count of the global executor freshly started with the given environment variable.
+fn thread_count_with_env(value: Option<&str>) -> usize {
+    fibers_global::reset_for_tests();
+    match value {
+        None => env::remove_var(THREADS_ENV),
+        Some(value) => env::set_var(THREADS_ENV, value),
+    }
+    let n = fibers_global::thread_count();
+    env::remove_var(THREADS_ENV);
+    n
+}
+
+#[test]
+fn env_thread_count_is_used_if_valid() {
+    let _guard = lock();
+    let default = thread_count_with_env(None);
+    assert_eq!(thread_count_with_env(Some("3")), 3);
+    assert_eq!(thread_count_with_env(Some(" 5 ")), 5);
+    assert_eq!(
+        thread_count_with_env(Some(&(default + 1).to_string())),
+        default + 1
+    );
+}
+
+#[test]
+fn env_thread_count_is_ignored_if_invalid() {
+    let _guard = lock();
+    let default = thread_count_with_env(None);
+    for value in ["", "0", "-1", "two", "1.5"] {
+        assert_eq!(thread_count_with_env(Some(value)), default, "{:?}", value);
+    }
+}
+
+#[test]
+fn explicit_thread_count_overrides_env() {
+    let _guard = lock();
+    fibers_global::reset_for_tests();
+    env::set_var(THREADS_ENV, "3");
+    assert!(fibers_global::set_thread_count(2));
+    assert_eq!(fibers_global::thread_count(), 2);
+    env::remove_var(THREADS_ENV);
+}
@@ -0,0 +1,21 @@
+#![cfg(all(feature = "test-reset", not(feature = "no-threads")))]
+
+use fibers::Spawn;
+use futures::lazy;
+
+#[test]
+fn handle_if_started_works() {
+    fibers_global::reset_for_tests();
+
+    // Does not start the executor.
+    assert!(fibers_global::handle_if_started().is_none());
+    assert!(!fibers_global::is_started());
+
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    let handle = fibers_global::handle_if_started().unwrap();
+    let monitor = handle.spawn_monitor(lazy(|| Ok::<_, ()>(2)));
+    assert_eq!(fibers_global::execute(monitor).ok(), Some(2));
+
+    fibers_global::shutdown();
+    assert!(fibers_global::handle_if_started().is_none());
+}
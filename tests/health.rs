@@ -0,0 +1,38 @@
+#![cfg(not(feature = "no-threads"))]
+
+use fibers_global::{ExecutorHealth, SpawnError};
+use futures::lazy;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn executor_health_works() {
+    assert_eq!(fibers_global::executor_health(), ExecutorHealth::NotStarted);
+    assert!(!fibers_global::is_started());
+
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    assert_eq!(fibers_global::executor_health(), ExecutorHealth::Running);
+
+    // A panicking fiber aborts the executor.
+    fibers_global::spawn(lazy(|| -> Result<(), ()> { panic!("health test") }));
+    let mut health = fibers_global::executor_health();
+    for _ in 0..1000 {
+        if health == ExecutorHealth::Dead {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+        health = fibers_global::executor_health();
+    }
+    assert_eq!(health, ExecutorHealth::Dead);
+    assert_eq!(
+        fibers_global::checked_spawn(lazy(|| Ok(()))),
+        Err(SpawnError::ExecutorDead)
+    );
+
+    fibers_global::shutdown();
+    assert_eq!(fibers_global::executor_health(), ExecutorHealth::NotStarted);
+
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(2))), Ok(2));
+    assert_eq!(fibers_global::executor_health(), ExecutorHealth::Running);
+    assert_eq!(fibers_global::checked_spawn(lazy(|| Ok(()))), Ok(()));
+}
@@ -0,0 +1,60 @@
+#![cfg(all(feature = "log", not(feature = "no-threads")))]
+
+use futures::lazy;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+struct CapturingLogger {
+    records: Mutex<Vec<(log::Level, String)>>,
+}
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let message = record.args().to_string();
+        self.records.lock().unwrap().push((record.level(), message));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    records: Mutex::new(Vec::new()),
+};
+
+fn find_error(pattern: &str) -> Option<String> {
+    LOGGER
+        .records
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(level, m)| *level == log::Level::Error && m.contains(pattern))
+        .map(|(_, m)| m.clone())
+}
+
+#[test]
+fn panics_are_logged() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let (id, _monitor) =
+        fibers_global::spawn_monitor_with_id(lazy(|| -> Result<(), ()> { panic!("log test") }));
+
+    let mut health = fibers_global::executor_health();
+    for _ in 0..1000 {
+        if health == fibers_global::ExecutorHealth::Dead {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+        health = fibers_global::executor_health();
+    }
+    assert_eq!(health, fibers_global::ExecutorHealth::Dead);
+
+    let panic = find_error("panicked").unwrap();
+    assert!(panic.contains(&id.to_string()), "{}", panic);
+    assert!(panic.contains("log test"), "{}", panic);
+    assert!(find_error("The global `ThreadPoolExecutor` aborted").is_some());
+}
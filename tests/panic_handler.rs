@@ -0,0 +1,61 @@
+#![cfg(not(feature = "no-threads"))]
+
+use futures::lazy;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn panic_handler_works() {
+    let (tx, rx) = mpsc::channel();
+    let tx = std::sync::Mutex::new(tx);
+    fibers_global::set_fiber_panic_handler(move |payload| {
+        let message = payload.downcast_ref::<&str>().copied().unwrap_or("");
+        let id = fibers_global::current_fiber_id();
+        let _ = tx.lock().unwrap().send((message.to_owned(), id));
+    });
+
+    // Non-panicking fibers are not affected.
+    let result = fibers_global::execute(fibers_global::spawn_monitor(lazy(|| Ok::<_, ()>(1))));
+    assert_eq!(result.ok(), Some(1));
+    assert!(rx.try_recv().is_err());
+
+    fibers_global::spawn(lazy(|| -> Result<(), ()> { panic!("spawn") }));
+    let (message, id) = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(message, "spawn");
+    // The `tracing` feature assigns identifiers to all fibers.
+    assert_eq!(id.is_some(), cfg!(feature = "tracing"));
+
+    // A panicking fiber aborts the executor, so it is restarted here.
+    fibers_global::shutdown();
+
+    let _ = fibers_global::spawn_monitor(lazy(|| -> Result<(), ()> { panic!("spawn_monitor") }));
+    let (message, id) = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(message, "spawn_monitor");
+    // The `tracing` feature assigns identifiers to all fibers.
+    assert_eq!(id.is_some(), cfg!(feature = "tracing"));
+    fibers_global::shutdown();
+
+    // Batches report panics too (the waiting thread panics since the executor aborts).
+    let futures = (0..2).map(|_| lazy(|| -> Result<(), ()> { panic!("execute_all_limited") }));
+    let _ = thread::spawn(move || fibers_global::execute_all_limited(futures, 1)).join();
+    let (message, _) = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(message, "execute_all_limited");
+    fibers_global::shutdown();
+
+    let futures = vec![lazy(|| -> Result<(), ()> {
+        panic!("execute_batch_unordered")
+    })];
+    let _ = thread::spawn(move || fibers_global::execute_batch_unordered(futures, |_| ())).join();
+    let (message, _) = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(message, "execute_batch_unordered");
+    fibers_global::shutdown();
+
+    // The handler can identify the fiber.
+    let (id, _monitor) =
+        fibers_global::spawn_monitor_with_id(lazy(|| -> Result<(), ()> { panic!("with_id") }));
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(10)).ok(),
+        Some(("with_id".to_owned(), Some(id)))
+    );
+}
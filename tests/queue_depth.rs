@@ -0,0 +1,31 @@
+#![cfg(not(feature = "no-threads"))]
+
+use futures::future::lazy;
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[test]
+fn queue_depth_rises_on_blocked_pool() {
+    fibers_global::set_thread_count(1);
+    assert_eq!(fibers_global::queue_depth(), 0);
+
+    // Blocks the only scheduler thread.
+    let (started_tx, started_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    fibers_global::spawn(lazy(move || {
+        let _ = started_tx.send(());
+        let _ = release_rx.recv();
+        Ok(())
+    }));
+    started_rx.recv().unwrap();
+
+    for _ in 0..3 {
+        fibers_global::spawn(lazy(|| Ok(())));
+    }
+    assert_eq!(fibers_global::queue_depth(), 3);
+    assert_eq!(fibers_global::metrics().queue_depth, 3);
+
+    release_tx.send(()).unwrap();
+    assert!(fibers_global::wait_for_idle(Duration::from_secs(10)));
+    assert_eq!(fibers_global::queue_depth(), 0);
+}
@@ -0,0 +1,15 @@
+#![cfg(all(feature = "test-reset", not(feature = "no-threads")))]
+
+use futures::lazy;
+
+#[test]
+fn reset_for_tests_works() {
+    for &n in &[2, 3, 2, 3] {
+        fibers_global::reset_for_tests();
+        assert!(!fibers_global::is_started());
+        assert!(fibers_global::set_thread_count(n));
+        assert_eq!(fibers_global::thread_count(), n);
+        assert_eq!(fibers_global::execute(lazy(move || Ok::<_, ()>(n))), Ok(n));
+        assert!(!fibers_global::set_thread_count(1));
+    }
+}
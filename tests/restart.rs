@@ -0,0 +1,50 @@
+#![cfg(not(feature = "no-threads"))]
+
+use fibers_global::{ExecuteError, GlobalExecutorBuilder};
+use futures::lazy;
+use std::thread;
+use std::time::Duration;
+
+fn panic_fiber() {
+    fibers_global::spawn(lazy(|| -> Result<(), ()> { panic!("restart test") }));
+}
+
+/// Executes a trivial future until `f` accepts the result.
+fn execute_until<F>(f: F) -> bool
+where
+    F: Fn(&Result<usize, ExecuteError<()>>) -> bool,
+{
+    for _ in 0..1000 {
+        if f(&fibers_global::execute_checked(lazy(|| Ok(1)))) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn executor_restarts_after_abort() {
+    assert!(GlobalExecutorBuilder::new()
+        .thread_count(2)
+        .max_restarts(1)
+        .try_init());
+    assert_eq!(
+        fibers_global::execute_checked(lazy(|| Ok::<_, ()>(1))),
+        Ok(1)
+    );
+
+    // The executor is rebuilt after the first abort.
+    panic_fiber();
+    thread::sleep(Duration::from_millis(100));
+    assert!(execute_until(|r| *r == Ok(1)));
+
+    // The restarts have been exhausted.
+    panic_fiber();
+    assert!(execute_until(|r| *r == Err(ExecuteError::Aborted)));
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(
+        fibers_global::execute_checked(lazy(|| Ok::<_, ()>(1))),
+        Err(ExecuteError::Aborted)
+    );
+}
@@ -0,0 +1,122 @@
+#![cfg(not(feature = "no-threads"))]
+
+use fibers::sync::oneshot;
+use fibers::Spawn;
+use futures::{empty, lazy, Future};
+use std::sync::{mpsc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+// Serializes the tests because they share the global executor.
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn lock() -> MutexGuard<'static, ()> {
+    LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[test]
+fn shutdown_works() {
+    let _guard = lock();
+
+    // Does nothing before the executor starts.
+    fibers_global::shutdown();
+
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    let thread_count = fibers_global::thread_count();
+
+    // A pending fiber and a thread waiting on it.
+    let (tx, rx) = oneshot::channel::<()>();
+    fibers_global::spawn(empty().then(move |r| {
+        let _ = tx;
+        r
+    }));
+    let (started_tx, started_rx) = mpsc::channel();
+    let waiting = thread::spawn(move || {
+        fibers_global::execute(lazy(move || started_tx.send(())).then(|_| empty::<(), ()>()))
+    });
+    started_rx.recv().unwrap();
+
+    fibers_global::shutdown();
+
+    // The pending fiber has been dropped.
+    assert!(waiting.join().is_err());
+    assert!(fibers_global::execute(rx).is_err());
+
+    // A fresh executor has been started.
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(2))), Ok(2));
+    assert_eq!(fibers_global::thread_count(), thread_count);
+}
+
+#[test]
+fn try_execute_result_reports_abort() {
+    let _guard = lock();
+
+    assert_eq!(
+        fibers_global::try_execute_result(lazy(|| Ok::<_, ()>(1))),
+        Ok(Ok(1))
+    );
+
+    let (started_tx, started_rx) = mpsc::channel();
+    let waiting = thread::spawn(move || {
+        fibers_global::try_execute_result(
+            lazy(move || started_tx.send(())).then(|_| empty::<(), ()>()),
+        )
+    });
+    started_rx.recv().unwrap();
+
+    // Shutting down drops the pending fiber just like an aborted executor does.
+    fibers_global::shutdown();
+    assert_eq!(waiting.join().unwrap(), Err(fibers_global::ExecutorAborted));
+}
+
+#[test]
+fn is_started_works() {
+    let _guard = lock();
+
+    fibers_global::shutdown();
+    assert!(!fibers_global::is_started());
+    assert!(!fibers_global::is_started());
+
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    assert!(fibers_global::is_started());
+
+    fibers_global::shutdown();
+    assert!(!fibers_global::is_started());
+}
+
+#[test]
+fn try_spawn_works() {
+    let _guard = lock();
+
+    fibers_global::shutdown();
+    assert_eq!(
+        fibers_global::try_spawn(futures::finished(())),
+        Err(fibers_global::NotStartedError)
+    );
+    assert!(!fibers_global::is_started());
+
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    let (tx, rx) = mpsc::channel();
+    let result = fibers_global::try_spawn(lazy(move || tx.send(()).map_err(|_| ())));
+    assert_eq!(result, Ok(()));
+    assert!(rx.recv().is_ok());
+}
+
+#[test]
+fn handle_is_renewed_after_shutdown() {
+    let _guard = lock();
+
+    let (tx, rx) = mpsc::channel();
+    fibers_global::handle().spawn(lazy(move || tx.send(1).map_err(|_| ())));
+    assert_eq!(rx.recv(), Ok(1));
+
+    // The handle cached by this thread belongs to the stopped executor.
+    fibers_global::shutdown();
+
+    let (tx, rx) = mpsc::channel();
+    fibers_global::handle().spawn(lazy(move || tx.send(2).map_err(|_| ())));
+    assert_eq!(rx.recv(), Ok(2));
+
+    let (tx, rx) = mpsc::channel();
+    fibers_global::spawn(lazy(move || tx.send(3).map_err(|_| ())));
+    assert_eq!(rx.recv(), Ok(3));
+}
@@ -0,0 +1,26 @@
+#![cfg(not(feature = "no-threads"))]
+
+use fibers::time::timer;
+use futures::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn shutdown_guard_drains_on_drop() {
+    let completed = Arc::new(AtomicUsize::new(0));
+    {
+        let guard = fibers_global::install_atexit_drain(Duration::from_secs(10));
+        assert_eq!(guard.timeout(), Duration::from_secs(10));
+        for _ in 0..10 {
+            let completed = Arc::clone(&completed);
+            fibers_global::spawn(timer::timeout(Duration::from_millis(20)).then(move |_| {
+                completed.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }));
+        }
+        assert!(fibers_global::is_started());
+    }
+    assert_eq!(completed.load(Ordering::SeqCst), 10);
+    assert!(!fibers_global::is_started());
+}
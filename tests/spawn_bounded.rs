@@ -0,0 +1,40 @@
+use fibers::time::timer;
+use futures::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn spawn_bounded_works() {
+    const MAX_IN_FLIGHT: usize = 4;
+
+    let max_observed = Arc::new(AtomicUsize::new(0));
+    let completed = Arc::new(AtomicUsize::new(0));
+    for _ in 0..100 {
+        let max_observed = Arc::clone(&max_observed);
+        let completed = Arc::clone(&completed);
+        let future = timer::timeout(Duration::from_millis(1))
+            .map(move |()| {
+                max_observed.fetch_max(fibers_global::in_flight_fibers(), Ordering::SeqCst);
+                completed.fetch_add(1, Ordering::SeqCst);
+            })
+            .map_err(|_| ());
+        fibers_global::spawn_bounded(future, MAX_IN_FLIGHT);
+        assert!(fibers_global::in_flight_fibers() <= MAX_IN_FLIGHT);
+    }
+
+    assert!(fibers_global::wait_for_idle(Duration::from_secs(10)));
+    assert_eq!(completed.load(Ordering::SeqCst), 100);
+    let max_observed = max_observed.load(Ordering::SeqCst);
+    assert!(
+        (1..=MAX_IN_FLIGHT).contains(&max_observed),
+        "{}",
+        max_observed
+    );
+}
+
+#[test]
+#[should_panic]
+fn spawn_bounded_rejects_zero() {
+    fibers_global::spawn_bounded(futures::finished(()), 0);
+}
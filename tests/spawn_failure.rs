@@ -0,0 +1,21 @@
+#![cfg(all(feature = "test-fault-injection", not(feature = "no-threads")))]
+
+use futures::lazy;
+use std::panic;
+
+#[test]
+fn thread_spawn_failure_is_reported() {
+    assert!(fibers_global::set_thread_count(3));
+
+    fibers_global::inject_thread_spawn_failure(true);
+    let payload = panic::catch_unwind(fibers_global::handle).err().unwrap();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(message.contains("thread_count=3"), "{}", message);
+    assert!(message.contains("injected failure"), "{}", message);
+    assert!(!fibers_global::is_started());
+
+    // The next use retries to start the executor.
+    fibers_global::inject_thread_spawn_failure(false);
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    assert_eq!(fibers_global::thread_count(), 3);
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "log")]
+
+use futures::future::{err, ok};
+use std::sync::Mutex;
+use std::time::Duration;
+
+struct CapturingLogger {
+    records: Mutex<Vec<(log::Level, String)>>,
+}
+impl log::Log for CapturingLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let message = record.args().to_string();
+        self.records.lock().unwrap().push((record.level(), message));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    records: Mutex::new(Vec::new()),
+};
+
+fn logged_errors() -> Vec<String> {
+    LOGGER
+        .records
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(level, _)| *level == log::Level::Error)
+        .map(|(_, m)| m.clone())
+        .collect()
+}
+
+#[test]
+fn spawn_logged_works() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    fibers_global::spawn_logged(ok::<_, &str>(1));
+    fibers_global::spawn_logged(err::<(), _>("spawn_logged test"));
+    assert!(fibers_global::wait_for_idle(Duration::from_secs(10)));
+
+    // The errors are reported before the fibers exit.
+    let errors = logged_errors();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].contains("spawn_logged test"), "{}", errors[0]);
+}
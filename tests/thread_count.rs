@@ -0,0 +1,28 @@
+#![cfg(not(feature = "no-threads"))]
+
+use futures::lazy;
+
+#[test]
+fn set_thread_count_works() {
+    // `usize::MAX` is no longer reserved.
+    assert!(fibers_global::set_thread_count(usize::MAX));
+
+    assert!(fibers_global::set_thread_count(2));
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    assert_eq!(fibers_global::thread_count(), 2);
+
+    // The executor already has started.
+    assert!(!fibers_global::set_thread_count(3));
+    assert_eq!(fibers_global::thread_count(), 2);
+
+    // The count is kept across restarts.
+    fibers_global::shutdown();
+    assert!(!fibers_global::set_thread_count(3));
+    assert_eq!(fibers_global::thread_count(), 2);
+}
+
+#[test]
+#[should_panic]
+fn set_thread_count_rejects_zero() {
+    fibers_global::set_thread_count(0);
+}
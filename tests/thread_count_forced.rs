@@ -0,0 +1,49 @@
+#![cfg(not(feature = "no-threads"))]
+
+use fibers::sync::oneshot;
+use fibers::time::timer;
+use futures::{lazy, Future};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn set_thread_count_forced_works() {
+    // Not fixed yet.
+    assert!(!fibers_global::set_thread_count_forced(1));
+    assert_eq!(fibers_global::thread_count(), 1);
+    assert!(fibers_global::spawn_on(1, lazy(|| Ok(()))).is_err());
+
+    // In-flight work is drained.
+    let completed = Arc::new(AtomicBool::new(false));
+    {
+        let completed = Arc::clone(&completed);
+        fibers_global::spawn(timer::timeout(Duration::from_millis(50)).then(move |_| {
+            completed.store(true, Ordering::SeqCst);
+            Ok(())
+        }));
+    }
+
+    assert!(fibers_global::set_thread_count_forced(3));
+    assert!(completed.load(Ordering::SeqCst));
+    assert!(fibers_global::is_started());
+    assert_eq!(fibers_global::thread_count(), 3);
+
+    // New work runs on the resized pool.
+    let (tx, rx) = oneshot::channel();
+    fibers_global::spawn_on(
+        2,
+        lazy(move || {
+            let _ = tx.send(fibers_global::on_pool_thread());
+            Ok(())
+        }),
+    )
+    .unwrap();
+    assert_eq!(fibers_global::execute(rx), Ok(true));
+
+    // After shutdown, the count is used at the next start.
+    fibers_global::shutdown();
+    assert!(!fibers_global::set_thread_count_forced(2));
+    assert!(!fibers_global::is_started());
+    assert_eq!(fibers_global::thread_count(), 2);
+}
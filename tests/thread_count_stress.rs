@@ -0,0 +1,50 @@
+#![cfg(not(feature = "no-threads"))]
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+#[test]
+fn set_thread_count_concurrently() {
+    const THREADS: usize = 16;
+
+    let barrier = Arc::new(Barrier::new(THREADS + 1));
+    let setters = (1..=THREADS)
+        .map(|n| {
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                let mut succeeded = false;
+                for _ in 0..1000 {
+                    if fibers_global::set_thread_count(n) {
+                        succeeded = true;
+                    } else {
+                        // Once the executor has started, every later call fails.
+                        assert!(!fibers_global::set_thread_count(n));
+                        break;
+                    }
+                }
+                (n, succeeded)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    barrier.wait();
+    let thread_count = fibers_global::thread_count();
+
+    let succeeded = setters
+        .into_iter()
+        .map(|t| t.join().unwrap())
+        .filter(|&(_, succeeded)| succeeded)
+        .map(|(n, _)| n)
+        .collect::<Vec<_>>();
+
+    // The executor uses the value of one of the successful calls.
+    assert!(
+        succeeded.contains(&thread_count)
+            || (succeeded.is_empty() && thread_count <= num_cpus::get()),
+        "thread_count={}, succeeded={:?}",
+        thread_count,
+        succeeded
+    );
+    assert!(!fibers_global::set_thread_count(1));
+}
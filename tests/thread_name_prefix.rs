@@ -0,0 +1,30 @@
+#![cfg(not(feature = "no-threads"))]
+
+use futures::lazy;
+
+#[test]
+fn set_thread_name_prefix_works() {
+    assert!(fibers_global::set_thread_name_prefix("custom"));
+    assert_eq!(
+        fibers_global::config_snapshot().thread_name_prefix,
+        "custom"
+    );
+
+    // `std::thread::current().name()` is not set for the scheduler threads, so the OS level name is read.
+    #[cfg(target_os = "linux")]
+    {
+        let name = fibers_global::execute(lazy(|| {
+            std::fs::read_to_string("/proc/thread-self/comm").map_err(|_| ())
+        }))
+        .unwrap();
+        assert!(name.starts_with("custom-"), "{}", name);
+    }
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+
+    // The executor has started.
+    assert!(!fibers_global::set_thread_name_prefix("other"));
+    assert_eq!(
+        fibers_global::config_snapshot().thread_name_prefix,
+        "custom"
+    );
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "tracing")]
+
+use fibers::sync::oneshot;
+use futures::{lazy, Future};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Captures the `id` fields of the created `fiber` spans.
+struct CapturingLayer {
+    fiber_ids: &'static Mutex<Vec<u64>>,
+}
+impl<S> Layer<S> for CapturingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes, _id: &Id, _ctx: Context<S>) {
+        struct IdVisitor(Option<u64>);
+        impl Visit for IdVisitor {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                if field.name() == "id" {
+                    self.0 = Some(value);
+                }
+            }
+
+            fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+        }
+
+        if attrs.metadata().name() == "fiber" {
+            let mut visitor = IdVisitor(None);
+            attrs.record(&mut visitor);
+            self.fiber_ids.lock().unwrap().extend(visitor.0);
+        }
+    }
+}
+
+static FIBER_IDS: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+#[test]
+fn fibers_are_polled_in_spans() {
+    let subscriber = tracing_subscriber::registry().with(CapturingLayer {
+        fiber_ids: &FIBER_IDS,
+    });
+    tracing::subscriber::set_global_default(subscriber).unwrap();
+
+    let current = || {
+        let span = tracing::Span::current();
+        let name = span.metadata().map(|m| m.name());
+        let id = fibers_global::current_fiber_id().map(|id| id.as_u64());
+        (name, id)
+    };
+
+    let (tx, rx) = oneshot::channel();
+    fibers_global::spawn(lazy(move || {
+        let _ = tx.send(current());
+        Ok(())
+    }));
+    let (name, spawn_id) = fibers_global::execute(rx).unwrap();
+    assert_eq!(name, Some("fiber"));
+
+    let monitor = fibers_global::spawn_monitor(lazy(move || Ok::<_, ()>(current())));
+    let (name, monitor_id) = fibers_global::execute(monitor).unwrap();
+    assert_eq!(name, Some("fiber"));
+
+    // The other spawning functions are instrumented too.
+    let (tx, rx) = oneshot::channel();
+    let result = fibers_global::try_spawn(lazy(move || {
+        let _ = tx.send(current());
+        Ok(())
+    }));
+    assert_eq!(result, Ok(()));
+    let (name, try_spawn_id) = fibers_global::execute(rx).unwrap();
+    assert_eq!(name, Some("fiber"));
+
+    let (tx, rx) = oneshot::channel();
+    fibers_global::spawn_bounded(
+        lazy(move || {
+            let _ = tx.send(current());
+            Ok(())
+        }),
+        usize::MAX,
+    );
+    let (name, bounded_id) = fibers_global::execute(rx).unwrap();
+    assert_eq!(name, Some("fiber"));
+
+    let (tx, rx) = oneshot::channel();
+    let result = fibers_global::spawn_on(
+        0,
+        lazy(move || {
+            let _ = tx.send(current());
+            Ok(())
+        }),
+    );
+    assert!(result.is_ok());
+    let (name, spawn_on_id) = fibers_global::execute(rx).unwrap();
+    assert_eq!(name, Some("fiber"));
+
+    let (name, into_global_id) = fibers_global::into_global(lazy(move || Ok::<_, ()>(current())))
+        .wait()
+        .unwrap();
+    assert_eq!(name, Some("fiber"));
+
+    let results =
+        fibers_global::execute_all_limited((0..2).map(|_| lazy(move || Ok::<_, ()>(current()))), 1)
+            .unwrap();
+    assert!(results.iter().all(|(name, _)| *name == Some("fiber")));
+
+    // The identifier returned to the caller is that of the span.
+    let (id, monitor) = fibers_global::spawn_monitor_with_id(lazy(move || Ok::<_, ()>(current())));
+    let (name, with_id) = fibers_global::execute(monitor).unwrap();
+    assert_eq!(name, Some("fiber"));
+    assert_eq!(with_id, Some(id.as_u64()));
+
+    // Outside of fibers.
+    assert_eq!(current(), (None, None));
+
+    let ids = FIBER_IDS.lock().unwrap();
+    assert!(ids.contains(&spawn_id.unwrap()), "{:?}", ids);
+    assert!(ids.contains(&monitor_id.unwrap()), "{:?}", ids);
+    let mut fiber_ids = vec![
+        try_spawn_id,
+        bounded_id,
+        spawn_on_id,
+        into_global_id,
+        with_id,
+    ];
+    fiber_ids.extend(results.iter().map(|(_, id)| *id));
+    for id in fiber_ids {
+        assert!(ids.contains(&id.unwrap()), "{:?}", ids);
+    }
+}
@@ -0,0 +1,34 @@
+use fibers::time::timer;
+use futures::{empty, Future};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn wait_for_idle_works() {
+    assert!(fibers_global::wait_for_idle(Duration::from_secs(10)));
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    for i in 0..10 {
+        let counter = Arc::clone(&counter);
+        fibers_global::spawn(
+            timer::timeout(Duration::from_millis(i * 5))
+                .map(move |()| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                })
+                .map_err(|_| ()),
+        );
+    }
+    assert!(fibers_global::wait_for_idle(Duration::from_secs(10)));
+    assert_eq!(counter.load(Ordering::SeqCst), 10);
+    assert_eq!(fibers_global::in_flight_fibers(), 0);
+
+    // A fiber that never completes.
+    fibers_global::spawn(empty());
+    assert_eq!(fibers_global::in_flight_fibers(), 1);
+    assert!(!fibers_global::wait_for_idle(Duration::from_millis(10)));
+
+    // Shutting down the executor drops the remaining fibers.
+    fibers_global::shutdown();
+    assert!(fibers_global::wait_for_idle(Duration::from_secs(10)));
+}
@@ -0,0 +1,25 @@
+#![cfg(not(feature = "no-threads"))]
+
+use futures::future::lazy;
+
+#[cfg(target_os = "linux")]
+fn os_thread_count() -> usize {
+    std::fs::read_dir("/proc/self/task").unwrap().count()
+}
+
+#[test]
+fn warm_up_works() {
+    assert!(!fibers_global::is_started());
+    fibers_global::warm_up();
+    assert!(fibers_global::is_started());
+
+    // Idempotent.
+    fibers_global::warm_up();
+
+    // No threads are spawned by `execute` after warming up.
+    #[cfg(target_os = "linux")]
+    let threads = os_thread_count();
+    assert_eq!(fibers_global::execute(lazy(|| Ok::<_, ()>(1))), Ok(1));
+    #[cfg(target_os = "linux")]
+    assert_eq!(os_thread_count(), threads);
+}